@@ -3,6 +3,7 @@ mod bgst_renderer;
 mod common;
 mod game;
 mod gfarch_utility;
+mod history;
 mod level_editor;
 mod settings;
 mod util;
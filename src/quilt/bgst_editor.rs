@@ -1,10 +1,11 @@
 use crate::quilt::game::bgst::LAYER_NAMES;
 
-use super::bgst_renderer::BGSTRenderer;
+use super::{bgst_renderer::BGSTRenderer, history::Undo};
 use anyhow::{Result, bail};
 use std::{fs, path::PathBuf};
 
 mod be_canvas;
+mod be_history;
 
 #[derive(Eq, PartialEq, Clone)]
 enum TileSelection {
@@ -18,6 +19,7 @@ pub struct BGSTEditor {
     selected_layer: i16,
     file_path: Option<PathBuf>,
     selected_tile: Option<TileSelection>,
+    history: be_history::BGSTHistory,
 }
 
 impl BGSTEditor {
@@ -29,14 +31,18 @@ impl BGSTEditor {
     }
 
     pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        self.bgst_renderer.poll(ui.ctx());
+        self.handle_history_shortcuts(ui.ctx());
+
         egui::TopBottomPanel::top("be_top_panel").show(ui.ctx(), |ui| {
             egui::menu::bar(ui, |ui| {
                 // file submenu
                 ui.menu_button("File", |ui| {
                     if ui.button("Open").clicked() {
-                        if let Ok(p) = self.bgst_renderer.open_file(ui) {
+                        if let Ok(p) = self.bgst_renderer.open_file() {
                             self.file_path = Some(p);
                             self.selected_tile = None;
+                            self.clear_history();
                         }
 
                         ui.close_menu();
@@ -66,6 +72,48 @@ impl BGSTEditor {
                         ui.close_menu();
                     }
                 });
+
+                ui.menu_button("Edit", |ui| {
+                    let undo_text = match self.undo_label() {
+                        Some(label) => format!("Undo {label}"),
+                        None => String::from("Undo"),
+                    };
+
+                    if ui
+                        .add_enabled(
+                            self.undo_label().is_some(),
+                            egui::Button::new(undo_text).shortcut_text("Ctrl+Z"),
+                        )
+                        .clicked()
+                    {
+                        self.undo(ui.ctx());
+                        ui.close_menu();
+                    }
+
+                    let redo_text = match self.redo_label() {
+                        Some(label) => format!("Redo {label}"),
+                        None => String::from("Redo"),
+                    };
+
+                    if ui
+                        .add_enabled(
+                            self.redo_label().is_some(),
+                            egui::Button::new(redo_text).shortcut_text("Ctrl+Y"),
+                        )
+                        .clicked()
+                    {
+                        self.redo(ui.ctx());
+                        ui.close_menu();
+                    }
+                });
+
+                if let Some(progress) = self.bgst_renderer.decode_progress() {
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .desired_width(200.0)
+                            .text("Decoding images"),
+                    );
+                }
             });
         });
 
@@ -105,6 +153,8 @@ impl BGSTEditor {
                     self.handle_selected_tile(ui);
                 });
             });
+
+            self.update_history(ui.ctx());
         }
     }
 
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::quilt::bgst_editor::TileSelection;
 
-use super::BGSTEditor;
+use super::{BGSTEditor, be_history::ImageChange};
 
 impl BGSTEditor {
     pub fn render_contents(&mut self, ui: &mut egui::Ui) {
@@ -17,6 +17,9 @@ impl BGSTEditor {
                         .show(ui, |ui| {
                             if let Some(bgst_file) = self.bgst_renderer.bgst_file.as_ref() {
                                 let image_render_size = egui::Vec2::splat(100.0);
+                                let lod = self.bgst_renderer.lod_for_size(image_render_size.x);
+                                let (grid_width, grid_height) =
+                                    (bgst_file.grid_width, bgst_file.grid_height);
 
                                 // we need to be able to draw empty squares should a coordinate not have
                                 // any associated cells
@@ -41,49 +44,72 @@ impl BGSTEditor {
 
                                 // render by y coordinate
 
-                                for y in 0..bgst_file.grid_height {
-                                    for x in 0..bgst_file.grid_width {
+                                for y in 0..grid_height {
+                                    for x in 0..grid_width {
                                         let coordinate = (y, x);
 
                                         if let Some(index) = entry_map.get(&coordinate) {
                                             // render filled square
-                                            if let Some(texture_handle) =
-                                                self.bgst_renderer.get_texture_handle(*index)
-                                            {
+                                            let texture = self.bgst_renderer.entry_texture(
+                                                ui.ctx(),
+                                                *index,
+                                                lod,
+                                            );
+
+                                            let resp = if let Some(texture) = texture {
                                                 // resize image because it's much too big
 
-                                                let image = egui::Image::new(texture_handle)
-                                                    .fit_to_exact_size(image_render_size);
+                                                let image = egui::Image::new(
+                                                    egui::load::SizedTexture::new(
+                                                        texture,
+                                                        image_render_size,
+                                                    ),
+                                                )
+                                                .fit_to_exact_size(image_render_size);
 
                                                 let image_button =
                                                     egui::ImageButton::new(image).frame(false); // disable frame, it's distracting
 
-                                                let resp = ui.add(image_button);
-
-                                                if resp.clicked() {
-                                                    self.selected_tile =
-                                                        Some(TileSelection::Entry(*index));
-                                                }
-
-                                                if let Some(tile) = &self.selected_tile
-                                                    && *tile == TileSelection::Entry(*index)
-                                                {
-                                                    ui.painter_at(resp.rect).rect_filled(
-                                                        resp.rect,
-                                                        0.0,
-                                                        egui::Color32::from_rgba_unmultiplied(
-                                                            0xFF, 0xFF, 0xFF, 0x10,
-                                                        ),
-                                                    );
-                                                } else if resp.hovered() {
-                                                    ui.painter_at(resp.rect).rect_filled(
-                                                        resp.rect,
-                                                        0.0,
-                                                        egui::Color32::from_rgba_unmultiplied(
-                                                            0xFF, 0xFF, 0xFF, 0x5,
-                                                        ),
-                                                    );
-                                                }
+                                                ui.add(image_button)
+                                            } else {
+                                                // still decoding
+                                                let (rect, resp) = ui.allocate_exact_size(
+                                                    image_render_size,
+                                                    egui::Sense::click(),
+                                                );
+
+                                                ui.painter_at(rect).rect_filled(
+                                                    rect,
+                                                    0.0,
+                                                    egui::Color32::from_gray(0x20),
+                                                );
+
+                                                resp
+                                            };
+
+                                            if resp.clicked() {
+                                                self.selected_tile =
+                                                    Some(TileSelection::Entry(*index));
+                                            }
+
+                                            if let Some(tile) = &self.selected_tile
+                                                && *tile == TileSelection::Entry(*index)
+                                            {
+                                                ui.painter_at(resp.rect).rect_filled(
+                                                    resp.rect,
+                                                    0.0,
+                                                    egui::Color32::from_rgba_unmultiplied(
+                                                        0xFF, 0xFF, 0xFF, 0x10,
+                                                    ),
+                                                );
+                                            } else if resp.hovered() {
+                                                ui.painter_at(resp.rect).rect_filled(
+                                                    resp.rect,
+                                                    0.0,
+                                                    egui::Color32::from_rgba_unmultiplied(
+                                                        0xFF, 0xFF, 0xFF, 0x5,
+                                                    ),
+                                                );
                                             }
                                         } else {
                                             // render empty square
@@ -208,10 +234,13 @@ impl BGSTEditor {
 
                         // main image
                         if ui.button("Replace Image").clicked() {
-                            let bgst_file = self.bgst_renderer.bgst_file.as_mut().unwrap();
-                            let entry = &bgst_file.bgst_entries[entry_index];
-                            let _ = bgst_file.replace_image(
-                                Some(entry.main_image_index as usize),
+                            let bgst_file = self.bgst_renderer.bgst_file.as_ref().unwrap();
+                            let main_image_index =
+                                bgst_file.bgst_entries[entry_index].main_image_index;
+
+                            self.replace_image(
+                                "Replace Image",
+                                main_image_index as usize,
                                 gctex::TextureFormat::CMPR,
                             );
 
@@ -220,7 +249,8 @@ impl BGSTEditor {
 
                         if ui.button("Remove Image").clicked() {
                             let bgst_file = self.bgst_renderer.bgst_file.as_mut().unwrap();
-                            bgst_file.remove_entry(entry_index);
+                            let removed = bgst_file.remove_entry(entry_index);
+                            self.record_edit("Remove Image", ImageChange::removed(removed));
                             self.selected_tile = None;
 
                             refresh = true;
@@ -239,42 +269,62 @@ impl BGSTEditor {
                         }
 
                         // mask image
-                        let bgst_file = self.bgst_renderer.bgst_file.as_mut().unwrap();
+                        let is_masked = self.bgst_renderer.bgst_file.as_ref().unwrap().bgst_entries
+                            [entry_index]
+                            .is_masked();
 
-                        if bgst_file.bgst_entries[entry_index].is_masked() {
+                        if is_masked {
                             if ui.button("Replace Mask").clicked() {
+                                let bgst_file = self.bgst_renderer.bgst_file.as_ref().unwrap();
                                 let mask_image_index =
                                     bgst_file.bgst_entries[entry_index].mask_image_index;
-                                let _ = bgst_file.replace_image(
-                                    Some(mask_image_index as usize),
+
+                                self.replace_image(
+                                    "Replace Mask",
+                                    mask_image_index as usize,
                                     gctex::TextureFormat::I4,
                                 );
 
                                 refresh = true;
                             }
 
-                            if ui.button("Remove Mask").clicked()
-                                && bgst_file.remove_entry_mask(entry_index)
-                            {
-                                refresh = true;
-                                image_removed = true;
+                            if ui.button("Remove Mask").clicked() {
+                                let bgst_file = self.bgst_renderer.bgst_file.as_mut().unwrap();
+
+                                if let Some(removed) = bgst_file.remove_entry_mask(entry_index) {
+                                    self.record_edit(
+                                        "Remove Mask",
+                                        ImageChange::removed([removed]),
+                                    );
+                                    refresh = true;
+                                    image_removed = true;
+
+                                    return; // from the closure
+                                }
 
-                                return; // from the closure
+                                // the mask is still used by other entries
+                                self.record_edit("Remove Mask", Vec::new());
+                                refresh = true;
                             }
 
                             if ui.button("Export Mask").clicked() {
+                                let bgst_file = self.bgst_renderer.bgst_file.as_ref().unwrap();
                                 let _ = bgst_file.export_image(
                                     bgst_file.bgst_entries[entry_index].mask_image_index as usize,
                                     gctex::TextureFormat::I4,
                                 );
                             }
-                        } else if ui.button("Add Mask").clicked()
-                            && let Ok(image_index) = bgst_file.add_image(gctex::TextureFormat::I4)
-                        {
-                            bgst_file.bgst_entries[entry_index].mask_image_index =
-                                image_index as i16;
+                        } else if ui.button("Add Mask").clicked() {
+                            let bgst_file = self.bgst_renderer.bgst_file.as_mut().unwrap();
 
-                            refresh = true;
+                            if let Ok(image_index) = bgst_file.add_image(gctex::TextureFormat::I4) {
+                                bgst_file.bgst_entries[entry_index].mask_image_index =
+                                    image_index as i16;
+
+                                let inserted = self.inserted_image(image_index);
+                                self.record_edit("Add Mask", inserted.into_iter().collect());
+                                refresh = true;
+                            }
                         }
 
                         let image_count = self
@@ -339,27 +389,35 @@ impl BGSTEditor {
                         let image_render_size = egui::Vec2::splat(100.0);
 
                         let bgst_file = self.bgst_renderer.bgst_file.as_ref().unwrap();
-                        let entry = &bgst_file.bgst_entries[entry_index];
+                        let entry = bgst_file.bgst_entries[entry_index];
+                        let lod = self.bgst_renderer.lod_for_size(image_render_size.x);
 
                         // show the main image
 
-                        let main_image_texture_handle = &self.bgst_renderer.decoded_image_handles
-                            [entry.main_image_index as usize];
-                        ui.add(
-                            egui::Image::new(main_image_texture_handle)
-                                .fit_to_exact_size(image_render_size),
-                        );
+                        let mut images = vec![entry.main_image_index];
 
                         if entry.is_masked() {
                             // show the mask
-                            let mask_image_texture_handle =
-                                &self.bgst_renderer.decoded_image_handles
-                                    [entry.mask_image_index as usize];
+                            images.push(entry.mask_image_index);
+                        }
 
-                            ui.add(
-                                egui::Image::new(mask_image_texture_handle)
+                        // mask-only entries have no main image
+                        for image_index in images.into_iter().filter(|i| *i >= 0) {
+                            if let Some(texture) = self.bgst_renderer.image_texture(
+                                ui.ctx(),
+                                image_index as usize,
+                                lod,
+                            ) {
+                                ui.add(
+                                    egui::Image::new(egui::load::SizedTexture::new(
+                                        texture,
+                                        image_render_size,
+                                    ))
                                     .fit_to_exact_size(image_render_size),
-                            );
+                                );
+                            } else {
+                                ui.spinner();
+                            }
                         }
                     }
                 });
@@ -368,26 +426,34 @@ impl BGSTEditor {
             TileSelection::Empty((y, x)) => {
                 ui.label(format!("X {x} Y {y}"));
                 if ui.button("Add Image").clicked() {
+                    let bgst_file = self.bgst_renderer.bgst_file.as_mut().unwrap();
+
                     // create new entry
-                    refresh = self
-                        .bgst_renderer
-                        .bgst_file
-                        .as_mut()
-                        .unwrap()
+                    refresh = bgst_file
                         .create_entry(self.selected_layer, (x as i16, y as i16))
                         .is_ok();
+
+                    if refresh {
+                        let image_index = bgst_file.compressed_images.len() - 1;
+                        let inserted = self.inserted_image(image_index);
+                        self.record_edit("Add Image", inserted.into_iter().collect());
+                    }
                 }
             }
         }
 
         if refresh {
-            let _ = self.bgst_renderer.cache_textures(ui.ctx());
+            self.bgst_renderer.refresh();
         }
     }
 
     pub fn display_image_list(&mut self, ui: &mut egui::Ui) {
         let bgst_file = self.bgst_renderer.bgst_file.as_ref().unwrap();
-        ui.label(format!("Count: {}", bgst_file.compressed_images.len()));
+        let image_count = bgst_file.compressed_images.len();
+        ui.label(format!("Count: {image_count}"));
+
+        let image_render_size = egui::Vec2::splat(100.0);
+        let lod = self.bgst_renderer.lod_for_size(image_render_size.x);
 
         let table = egui_extras::TableBuilder::new(ui)
             .striped(true)
@@ -395,20 +461,30 @@ impl BGSTEditor {
             .column(egui_extras::Column::auto()) // image column
             .column(egui_extras::Column::auto()); // index column
 
-        table.body(|mut body| {
-            for (index, image_handle) in self.bgst_renderer.decoded_image_handles.iter().enumerate()
-            {
-                body.row(32.0, |mut row| {
-                    // image column
-                    row.col(|ui| {
-                        ui.add(egui::Image::new(image_handle).max_size(egui::Vec2::splat(100.0)));
-                    });
-                    // index column
-                    row.col(|ui| {
-                        ui.label(format!("Index {index}"));
-                    });
+        table.body(|body| {
+            // only rows that are scrolled into view are drawn, so only their textures get uploaded
+            body.rows(32.0, image_count, |mut row| {
+                let index = row.index();
+
+                // image column
+                row.col(|ui| {
+                    if let Some(texture) = self.bgst_renderer.image_texture(ui.ctx(), index, lod) {
+                        ui.add(
+                            egui::Image::new(egui::load::SizedTexture::new(
+                                texture,
+                                image_render_size,
+                            ))
+                            .max_size(image_render_size),
+                        );
+                    } else {
+                        ui.spinner();
+                    }
                 });
-            }
+                // index column
+                row.col(|ui| {
+                    ui.label(format!("Index {index}"));
+                });
+            });
         });
     }
 }
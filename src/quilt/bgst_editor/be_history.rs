@@ -0,0 +1,201 @@
+use super::{BGSTEditor, TileSelection};
+use crate::quilt::{
+    game::bgst::BGSTEntry,
+    history::{Undo, UndoStack},
+};
+
+/// A change to one image, in the order it was made.
+/// Only the images an edit touches are kept, since they're much larger than the entries.
+pub enum ImageChange {
+    Insert(usize, Vec<u8>),
+    Remove(usize, Vec<u8>),
+    Replace {
+        index: usize,
+        before: Vec<u8>,
+        after: Vec<u8>,
+    },
+}
+
+impl ImageChange {
+    /// The changes made by removing images, as returned by `BGSTFile::remove_entry`.
+    pub fn removed(images: impl IntoIterator<Item = (usize, Vec<u8>)>) -> Vec<Self> {
+        images
+            .into_iter()
+            .map(|(index, image)| Self::Remove(index, image))
+            .collect()
+    }
+
+    fn apply(&self, images: &mut Vec<Vec<u8>>, reverse: bool) {
+        match (self, reverse) {
+            (Self::Insert(index, image), false) | (Self::Remove(index, image), true) => {
+                images.insert(*index, image.clone());
+            }
+
+            (Self::Insert(index, _), true) | (Self::Remove(index, _), false) => {
+                images.remove(*index);
+            }
+
+            (Self::Replace { index, before, .. }, true) => images[*index] = before.clone(),
+            (Self::Replace { index, after, .. }, false) => images[*index] = after.clone(),
+        }
+    }
+}
+
+/// A single step in the history.
+/// Entries are small, so all of them are kept.
+pub struct BGSTEdit {
+    label: String,
+    entries_before: Vec<BGSTEntry>,
+    entries_after: Vec<BGSTEntry>,
+    image_changes: Vec<ImageChange>,
+}
+
+#[derive(Default)]
+pub struct BGSTHistory {
+    steps: UndoStack<BGSTEdit>,
+    /// The entries as of the last recorded step.
+    entries: Vec<BGSTEntry>,
+}
+
+impl BGSTEditor {
+    /// Forgets every step. This is done whenever a file is opened.
+    pub fn clear_history(&mut self) {
+        self.history = BGSTHistory {
+            entries: self
+                .bgst_renderer
+                .bgst_file
+                .as_ref()
+                .map(|f| f.bgst_entries.clone())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+    }
+
+    /// The change made by adding the image at `index`.
+    pub fn inserted_image(&self, index: usize) -> Option<ImageChange> {
+        let bgst_file = self.bgst_renderer.bgst_file.as_ref()?;
+        let image = bgst_file.compressed_images.get(index)?;
+
+        Some(ImageChange::Insert(index, image.clone()))
+    }
+
+    /// Replaces an image using a file dialog, and records the change.
+    pub fn replace_image(&mut self, label: &str, index: usize, format: gctex::TextureFormat) {
+        let Some(bgst_file) = self.bgst_renderer.bgst_file.as_mut() else {
+            return;
+        };
+
+        let Some(before) = bgst_file.compressed_images.get(index).cloned() else {
+            return;
+        };
+
+        if bgst_file.replace_image(Some(index), format).is_ok() {
+            let after = bgst_file.compressed_images[index].clone();
+            self.record_edit(
+                label,
+                vec![ImageChange::Replace {
+                    index,
+                    before,
+                    after,
+                }],
+            );
+        }
+    }
+
+    /// Records the changes made to the entries since the last step, along with `image_changes`.
+    /// Nothing is recorded if nothing changed.
+    pub fn record_edit(&mut self, label: &str, image_changes: Vec<ImageChange>) {
+        let Some(bgst_file) = self.bgst_renderer.bgst_file.as_ref() else {
+            return;
+        };
+
+        let entries_after = bgst_file.bgst_entries.clone();
+
+        if entries_after == self.history.entries && image_changes.is_empty() {
+            return;
+        }
+
+        let entries_before = std::mem::replace(&mut self.history.entries, entries_after.clone());
+
+        self.history.steps.push(BGSTEdit {
+            label: label.to_string(),
+            entries_before,
+            entries_after,
+            image_changes,
+        });
+    }
+
+    /// Records edits made to entry fields, once nothing is being dragged.
+    pub fn update_history(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
+
+        self.record_edit("Edit Entry", Vec::new());
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.history
+            .steps
+            .undo_stack
+            .last()
+            .map(|e| e.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.history
+            .steps
+            .redo_stack
+            .last()
+            .map(|e| e.label.as_str())
+    }
+}
+
+impl Undo for BGSTEditor {
+    type Step = BGSTEdit;
+
+    fn steps(&mut self) -> &mut UndoStack<BGSTEdit> {
+        &mut self.history.steps
+    }
+
+    fn record_pending(&mut self) {
+        self.record_edit("Edit Entry", Vec::new());
+    }
+
+    fn apply_step(&mut self, _ctx: &egui::Context, edit: &BGSTEdit, reverse: bool) {
+        let Some(bgst_file) = self.bgst_renderer.bgst_file.as_mut() else {
+            return;
+        };
+
+        let entries = if reverse {
+            for change in edit.image_changes.iter().rev() {
+                change.apply(&mut bgst_file.compressed_images, true);
+            }
+
+            &edit.entries_before
+        } else {
+            for change in edit.image_changes.iter() {
+                change.apply(&mut bgst_file.compressed_images, false);
+            }
+
+            &edit.entries_after
+        };
+
+        bgst_file.bgst_entries = entries.clone();
+        self.history.entries = entries.clone();
+
+        // the selected entry may not exist anymore
+        if let Some(TileSelection::Entry(index)) = self.selected_tile
+            && index >= entries.len()
+        {
+            self.selected_tile = None;
+        }
+
+        // images that didn't change keep their textures
+        self.bgst_renderer.refresh();
+    }
+
+    fn has_history(&self) -> bool {
+        self.bgst_renderer.bgst_file.is_some()
+    }
+}
@@ -1,22 +1,189 @@
-use crate::quilt::game::bgst::*;
+use crate::quilt::{
+    common::{Camera, Point2D},
+    game::bgst::*,
+};
 use anyhow::{Result, anyhow, bail};
 use egui::TextureOptions;
 use image::{ImageBuffer, RgbaImage};
 use rayon::prelude::*;
 use rfd::FileDialog;
-use std::{collections::HashMap, fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, hash_map::DefaultHasher},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, TryRecvError},
+    },
+    thread,
+};
+
+/// The number of sizes each image is kept at. Each level is half the size of the previous one.
+const LOD_COUNT: usize = 4;
+
+/// The most textures uploaded in a single frame, so that panning into an
+/// unseen area doesn't stall the UI.
+const MAX_UPLOADS_PER_FRAME: usize = 16;
+
+/// Textures that haven't been drawn for this many frames are freed.
+const TEXTURE_LIFETIME: u64 = 300;
+
+#[derive(Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlacementMode {
+    /// The placement is computed from the BGST header.
+    #[default]
+    Derived,
+    /// The placement uses manually calibrated values.
+    Calibrated,
+}
+
+/// Manually entered placement values, for backgrounds the derived placement gets wrong.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    /// The size of one grid cell in world units.
+    pub tile_size: f32,
+    /// World-space offset from the `BG_BASE` gimmick.
+    pub offset: Point2D,
+    pub scale: Point2D,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            tile_size: 11.9,
+            offset: Point2D::default(),
+            scale: Point2D { x: 1.028, y: 1.019 },
+        }
+    }
+}
+
+/// Describes where a BGST is placed in the level editor's world.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BGSTAlignment {
+    pub mode: PlacementMode,
+    /// Used to size tiles when the placement is derived from the BGST header.
+    #[serde(default = "BGSTAlignment::default_pixels_per_unit")]
+    pub pixels_per_unit: f32,
+    pub calibration: Calibration,
+    /// Parallax factor of each layer, indexed the same way as `LAYER_NAMES`.
+    pub parallax: [f32; 12],
+}
+
+impl BGSTAlignment {
+    fn default_pixels_per_unit() -> f32 {
+        DEFAULT_PIXELS_PER_WORLD_UNIT
+    }
+}
+
+impl Default for BGSTAlignment {
+    fn default() -> Self {
+        Self {
+            mode: PlacementMode::Derived,
+            pixels_per_unit: DEFAULT_PIXELS_PER_WORLD_UNIT,
+            calibration: Calibration::default(),
+            parallax: LAYER_PARALLAX,
+        }
+    }
+}
+
+/// Identifies a decoded image by its contents rather than by its index,
+/// so that it survives images being added, removed or replaced.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum TextureKey {
+    Image(u64),
+    /// A main image with a mask applied to it.
+    Masked(u64, u64),
+}
+
+/// A decoded image along with progressively smaller copies of it.
+struct DecodedImage {
+    /// The first level is the full image.
+    levels: Vec<([usize; 2], Vec<u8>)>,
+}
+
+impl DecodedImage {
+    fn new(width: usize, height: usize, rgba: Vec<u8>) -> Self {
+        let mut levels = vec![([width, height], rgba)];
+
+        for _ in 1..LOD_COUNT {
+            let ([width, height], rgba) = levels.last().unwrap();
+
+            if *width < 2 || *height < 2 {
+                break;
+            }
+
+            let half = Self::half_size(rgba, *width, *height);
+            levels.push(([width / 2, height / 2], half));
+        }
+
+        Self { levels }
+    }
+
+    /// Downscales an image by averaging every 2x2 block of pixels.
+    fn half_size(rgba: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let (half_width, half_height) = (width / 2, height / 2);
+        let mut out = Vec::with_capacity(half_width * half_height * 4);
+
+        for y in 0..half_height {
+            for x in 0..half_width {
+                for channel in 0..4 {
+                    let sum: u32 = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                        .iter()
+                        .map(|(dx, dy)| {
+                            rgba[((y * 2 + dy) * width + (x * 2 + dx)) * 4 + channel] as u32
+                        })
+                        .sum();
+
+                    out.push((sum / 4) as u8);
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Images being decoded on a background thread.
+struct DecodeJob {
+    receiver: mpsc::Receiver<(TextureKey, Option<Arc<DecodedImage>>)>,
+    cancelled: Arc<AtomicBool>,
+    total: usize,
+    done: usize,
+}
+
+impl Drop for DecodeJob {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+struct CachedTexture {
+    handle: egui::TextureHandle,
+    last_used: u64,
+}
 
 #[derive(Default)]
 pub struct BGSTRenderer {
     pub bgst_file: Option<BGSTFile>,
-    pub decoded_image_handles: Vec<egui::TextureHandle>,
-    pub raw_image_data: HashMap<egui::TextureId, Vec<u8>>,
-    pub masked_textures: HashMap<(usize, usize), egui::TextureHandle>,
-    pub tile_size: f32,
-    pub tile_offset: egui::Vec2,
-    pub tile_scale: egui::Vec2,
+    pub alignment: BGSTAlignment,
     pub opacity: u8,
-    pub zoom: f32,
+    /// Whether each layer is drawn in the level editor, indexed the same way as `LAYER_NAMES`.
+    pub visible_layers: [bool; 12],
+    pub parallax_enabled: bool,
+
+    /// The content key of each compressed image.
+    image_keys: Vec<u64>,
+    decoded_images: HashMap<TextureKey, Arc<DecodedImage>>,
+    decode_job: Option<DecodeJob>,
+    /// Uploaded textures, by image and level of detail.
+    textures: HashMap<(TextureKey, usize), CachedTexture>,
+    /// Entry indices sorted by the order they're drawn in.
+    draw_list: Vec<usize>,
+    frame: u64,
+    uploads_this_frame: usize,
 }
 
 impl BGSTRenderer {
@@ -50,49 +217,22 @@ impl BGSTRenderer {
         Ok(output_bytes)
     }
 
-    pub fn get_raw_image_by_texture_handle(
-        &self,
-        tex_handle: &egui::TextureHandle,
-    ) -> Result<Vec<u8>> {
-        let handle_id = tex_handle.id();
-
-        if let Some(image_data) = self.raw_image_data.get(&handle_id) {
-            Ok(image_data.clone())
-        } else {
-            bail!(
-                "no raw image data found for texture handle id {:?}",
-                handle_id
-            );
-        }
-    }
-
     pub fn new() -> Self {
         Self {
-            tile_size: 11.9,
-            // tile_size: 10.0,
-            tile_scale: egui::Vec2::new(1.028, 1.019),
-            // tile_scale: egui::Vec2::splat(1.0),
-            tile_offset: egui::Vec2::splat(0.0),
             opacity: 128,
-            zoom: 1.0,
+            visible_layers: [true; 12],
+            parallax_enabled: true,
 
             ..Default::default()
         }
     }
 
-    pub fn open_file(&mut self, ui: &egui::Ui) -> Result<PathBuf> {
+    pub fn open_file(&mut self) -> Result<PathBuf> {
         if let Some(path) = FileDialog::new()
             .add_filter("BGST file", &["bgst3"])
             .pick_file()
         {
-            let data = fs::read(&path)?;
-
-            if data.is_empty() || b"BGST" != &data[..4] {
-                bail!("bgst is invalid");
-            }
-
-            self.bgst_file = Some(BGSTFile::decode(&data));
-            let _ = self.cache_textures(ui.ctx());
+            self.load_file(&path)?;
 
             Ok(path)
         } else {
@@ -100,254 +240,526 @@ impl BGSTRenderer {
         }
     }
 
-    /// Decodes and caches images
-    pub fn cache_textures(&mut self, ctx: &egui::Context) -> Result<()> {
-        // put this here
-        let bgst_file = match self.bgst_file.as_ref() {
-            Some(file) => file,
-            None => bail!("BGST file not loaded"),
+    pub fn load_file(&mut self, path: &Path) -> Result<()> {
+        let data = fs::read(path)?;
+
+        if data.len() < 4 || b"BGST" != &data[..4] {
+            bail!("bgst is invalid");
+        }
+
+        self.bgst_file = Some(BGSTFile::decode(&data));
+        self.refresh();
+
+        Ok(())
+    }
+
+    pub fn close_file(&mut self) {
+        self.bgst_file = None;
+        self.refresh();
+    }
+
+    fn image_format(bgst_file: &BGSTFile, index: usize) -> gctex::TextureFormat {
+        let is_main = bgst_file
+            .bgst_entries
+            .iter()
+            .any(|entry| entry.main_image_index > -1 && entry.main_image_index as usize == index);
+
+        let is_mask = bgst_file
+            .bgst_entries
+            .iter()
+            .any(|entry| entry.mask_image_index > -1 && entry.mask_image_index as usize == index);
+
+        match (is_main, is_mask) {
+            (true, _) => gctex::TextureFormat::CMPR, // "main" image
+            (_, true) => gctex::TextureFormat::I4,   // mask image
+            _ => gctex::TextureFormat::CMPR,         // default
+        }
+    }
+
+    fn image_key(encoded: &[u8], format: gctex::TextureFormat) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        encoded.hash(&mut hasher);
+        (format as u32).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Brings the decoded images up to date with the BGST file.
+    /// Images that were decoded before are reused; the rest are decoded on a background thread.
+    pub fn refresh(&mut self) {
+        let Some(bgst_file) = self.bgst_file.as_ref() else {
+            self.image_keys.clear();
+            self.decoded_images.clear();
+            self.textures.clear();
+            self.draw_list.clear();
+            self.decode_job = None;
+            return;
         };
 
-        // clear old data
-        self.decoded_image_handles.clear();
-        self.masked_textures.clear();
-        self.raw_image_data.clear();
+        let formats: Vec<gctex::TextureFormat> = (0..bgst_file.compressed_images.len())
+            .map(|index| Self::image_format(bgst_file, index))
+            .collect();
 
-        let results: Vec<(egui::TextureHandle, Vec<u8>)> = bgst_file
+        self.image_keys = bgst_file
             .compressed_images
             .par_iter()
-            .enumerate()
-            .map(|(index, encoded)| {
-                let tex_format = {
-                    let is_main = bgst_file.bgst_entries.iter().any(|entry| {
-                        entry.main_image_index > -1 && entry.main_image_index as usize == index
-                    });
-                    let is_mask = bgst_file.bgst_entries.iter().any(|entry| {
-                        entry.mask_image_index > -1 && entry.mask_image_index as usize == index
-                    });
-                    match (is_main, is_mask) {
-                        (true, _) => gctex::TextureFormat::CMPR, // "main" image
-                        (_, true) => gctex::TextureFormat::I4,   // mask image
-                        _ => gctex::TextureFormat::CMPR,         // default
-                    }
-                };
-                let decoded = gctex::decode(
-                    encoded,
-                    bgst_file.image_width,
-                    bgst_file.image_height,
-                    tex_format,
-                    &Vec::new(),
-                    0,
-                );
-                let handle = self.load_texture_handle(
-                    ctx,
-                    bgst_file.image_width as usize,
-                    bgst_file.image_height as usize,
-                    index,
-                    &decoded,
-                );
-                (handle, decoded)
-            })
+            .zip(formats.par_iter())
+            .map(|(encoded, format)| Self::image_key(encoded, *format))
             .collect();
 
-        for (handle, decoded) in results {
-            self.raw_image_data.insert(handle.id(), decoded);
-            self.decoded_image_handles.push(handle);
-        }
-        // determine which entries have masks
-        let (masked, _): (Vec<BGSTEntry>, Vec<_>) = bgst_file
+        let masked_keys: HashSet<(u64, u64)> = bgst_file
             .bgst_entries
             .iter()
-            .partition(|entry| entry.main_image_index > -1 && entry.mask_image_index > -1);
-        let num_images = self.decoded_image_handles.len();
-        // cache the masked entries
-        let masked_results: Vec<((usize, usize), egui::TextureHandle)> = masked
-            .into_par_iter()
+            .filter(|entry| entry.is_masked())
             .filter_map(|entry| {
-                let main_index = entry.main_image_index as usize;
-                let mask_index = entry.mask_image_index as usize;
-                if main_index >= num_images || mask_index >= num_images {
-                    // they're invalid
+                let main = self.image_keys.get(entry.main_image_index as usize)?;
+                let mask = self.image_keys.get(entry.mask_image_index as usize)?;
+                Some((*main, *mask))
+            })
+            .collect();
+
+        let wanted: HashSet<TextureKey> = self
+            .image_keys
+            .iter()
+            .map(|key| TextureKey::Image(*key))
+            .chain(
+                masked_keys
+                    .iter()
+                    .map(|(main, mask)| TextureKey::Masked(*main, *mask)),
+            )
+            .collect();
+
+        // forget images that are no longer part of the file
+        self.decoded_images.retain(|key, _| wanted.contains(key));
+        self.textures.retain(|(key, _), _| wanted.contains(key));
+
+        let mut queued = HashSet::new();
+
+        let images: Vec<(TextureKey, Vec<u8>, gctex::TextureFormat)> = bgst_file
+            .compressed_images
+            .iter()
+            .zip(formats)
+            .zip(self.image_keys.iter())
+            .filter_map(|((encoded, format), key)| {
+                let key = TextureKey::Image(*key);
+
+                if self.decoded_images.contains_key(&key) || !queued.insert(key) {
                     return None;
                 }
-                let main_handle = &self.decoded_image_handles[main_index];
-                let mask_handle = &self.decoded_image_handles[mask_index];
-                let main_image = match self.get_raw_image_by_texture_handle(main_handle) {
-                    Ok(img) => img,
-                    Err(_) => return None,
-                };
-                let mask_image = match self.get_raw_image_by_texture_handle(mask_handle) {
-                    Ok(img) => img,
-                    Err(_) => return None,
-                };
-                // apply masks
-                let masked_image = match BGSTRenderer::apply_mask(
-                    &main_image,
-                    &mask_image,
-                    bgst_file.image_width,
-                    bgst_file.image_height,
-                ) {
-                    Ok(img) => img,
-                    Err(_) => return None,
-                };
-                // load textures
-                let masked_texture = ctx.load_texture(
-                    format!("be_masked_tex_{}-{}", main_index, mask_index),
-                    egui::ColorImage::from_rgba_unmultiplied(
-                        [
-                            bgst_file.image_width as usize,
-                            bgst_file.image_height as usize,
-                        ],
-                        &masked_image,
-                    ),
-                    TextureOptions::LINEAR,
-                );
-                Some(((main_index, mask_index), masked_texture))
+
+                Some((key, encoded.clone(), format))
+            })
+            .collect();
+
+        let masks: Vec<(TextureKey, TextureKey, TextureKey)> = masked_keys
+            .iter()
+            .map(|(main, mask)| {
+                (
+                    TextureKey::Masked(*main, *mask),
+                    TextureKey::Image(*main),
+                    TextureKey::Image(*mask),
+                )
             })
+            .filter(|(key, _, _)| !self.decoded_images.contains_key(key))
             .collect();
 
-        for ((main_index, mask_index), masked_texture) in masked_results {
-            self.masked_textures
-                .insert((main_index, mask_index), masked_texture);
+        let width = bgst_file.image_width;
+        let height = bgst_file.image_height;
+
+        self.rebuild_draw_list();
+
+        if images.is_empty() && masks.is_empty() {
+            self.decode_job = None;
+            return;
         }
 
-        Ok(())
+        // masks may be applied to images that were decoded before
+        let known: HashMap<TextureKey, Arc<DecodedImage>> = masks
+            .iter()
+            .flat_map(|(_, main, mask)| [*main, *mask])
+            .filter_map(|key| Some((key, self.decoded_images.get(&key)?.clone())))
+            .collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        self.decode_job = Some(DecodeJob {
+            receiver,
+            cancelled: cancelled.clone(),
+            total: images.len() + masks.len(),
+            done: 0,
+        });
+
+        thread::spawn(move || {
+            let mut known = known;
+
+            let decoded: Vec<(TextureKey, Arc<DecodedImage>)> = images
+                .into_par_iter()
+                .map_with(sender.clone(), |sender, (key, encoded, format)| {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return None;
+                    }
+
+                    let rgba = gctex::decode(&encoded, width, height, format, &Vec::new(), 0);
+                    let image = Arc::new(DecodedImage::new(width as usize, height as usize, rgba));
+
+                    let _ = sender.send((key, Some(image.clone())));
+                    Some((key, image))
+                })
+                .flatten()
+                .collect();
+
+            known.extend(decoded);
+
+            masks
+                .into_par_iter()
+                .for_each_with(sender, |sender, (key, main, mask)| {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+
+                    let masked = known
+                        .get(&main)
+                        .zip(known.get(&mask))
+                        .and_then(|(main, mask)| {
+                            BGSTRenderer::apply_mask(
+                                &main.levels[0].1,
+                                &mask.levels[0].1,
+                                width,
+                                height,
+                            )
+                            .ok()
+                        });
+
+                    let image = masked.map(|rgba| {
+                        Arc::new(DecodedImage::new(width as usize, height as usize, rgba))
+                    });
+
+                    let _ = sender.send((key, image));
+                });
+        });
     }
 
-    fn load_texture_handle(
-        &self,
-        ctx: &egui::Context,
-        width: usize,
-        height: usize,
-        index: usize,
-        decoded: &[u8],
-    ) -> egui::TextureHandle {
-        let texture = egui::ColorImage::from_rgba_unmultiplied([width, height], decoded);
-
-        ctx.load_texture(
-            format!("le_bgst_image-{}", index),
-            texture,
-            egui::TextureOptions::LINEAR,
-        )
+    /// Sorts entries by layer, drawing unmasked entries before masked ones within a layer.
+    fn rebuild_draw_list(&mut self) {
+        let Some(bgst_file) = self.bgst_file.as_ref() else {
+            self.draw_list.clear();
+            return;
+        };
+
+        self.draw_list = (0..bgst_file.bgst_entries.len()).collect();
+        self.draw_list.sort_by_key(|index| {
+            let entry = &bgst_file.bgst_entries[*index];
+            (entry.layer, entry.is_masked())
+        });
     }
 
-    pub fn get_texture_handle(&self, index: usize) -> Option<&egui::TextureHandle> {
-        if let Some(bgst_file) = self.bgst_file.as_ref() {
-            let entry = &bgst_file.bgst_entries[index];
+    /// Receives images decoded in the background and frees unused textures.
+    /// This should be called once per frame.
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        self.frame += 1;
+        self.uploads_this_frame = 0;
 
-            let main_index = entry.main_image_index as usize;
-            let mask_index = entry.mask_image_index as usize;
+        if let Some(job) = self.decode_job.as_mut() {
+            let mut disconnected = false;
 
-            if entry.is_masked() {
-                Some(self.masked_textures.get(&(main_index, mask_index)).unwrap())
+            loop {
+                match job.receiver.try_recv() {
+                    Ok((key, image)) => {
+                        job.done += 1;
+
+                        if let Some(image) = image {
+                            self.decoded_images.insert(key, image);
+                        }
+                    }
+
+                    Err(TryRecvError::Empty) => break,
+
+                    Err(TryRecvError::Disconnected) => {
+                        disconnected = true;
+                        break;
+                    }
+                }
+            }
+
+            if disconnected || job.done >= job.total {
+                self.decode_job = None;
             } else {
-                Some(&self.decoded_image_handles[main_index])
+                ctx.request_repaint();
             }
-        } else {
-            None
         }
-    }
-    /// Rendering function for the level editor
-    pub fn le_render(&self, ui: &mut egui::Ui, rect: egui::Rect, position: egui::Vec2) {
-        if self.bgst_file.is_none() {
-            return;
+
+        if let Some(bgst_file) = self.bgst_file.as_ref()
+            && bgst_file.bgst_entries.len() != self.draw_list.len()
+        {
+            self.rebuild_draw_list();
         }
 
-        let bgst_file = self.bgst_file.as_ref().unwrap();
+        let frame = self.frame;
+        self.textures
+            .retain(|_, texture| frame - texture.last_used < TEXTURE_LIFETIME);
+    }
 
-        // collect entries based on whether or not
-        // a mask is applied
+    /// The fraction of images that have been decoded, if images are still being decoded.
+    pub fn decode_progress(&self) -> Option<f32> {
+        self.decode_job
+            .as_ref()
+            .map(|job| job.done as f32 / job.total as f32)
+    }
 
-        let (mut masked, mut unmasked): (Vec<BGSTEntry>, Vec<BGSTEntry>) = bgst_file
-            .bgst_entries
-            .iter()
-            .partition(|entry| entry.is_masked());
+    /// Picks the level of detail for a tile drawn at the given size in points.
+    pub fn lod_for_size(&self, size: f32) -> usize {
+        let full_size = self.bgst_file.as_ref().map(|f| f.image_width).unwrap_or(1) as f32;
 
-        // sort both vectors by entry layer
-        masked.sort_by_key(|a| a.layer);
-        unmasked.sort_by_key(|a| a.layer);
+        let mut lod = 0;
 
-        let grid_origin = egui::Vec2::new(
-            position.x - self.tile_offset.x,
-            position.y
-                - (self.tile_size * self.zoom * bgst_file.grid_height as f32)
-                - self.tile_offset.y,
-        );
+        while lod + 1 < LOD_COUNT && size * 2.0 <= full_size / (1 << lod) as f32 {
+            lod += 1;
+        }
 
-        // println!("BGSTRenderer::le_render - grid_origin: {}", grid_origin);
+        lod
+    }
 
-        // render unmasked
-        for entry in unmasked.iter() {
-            self.render_unmasked_entry(ui, rect, entry, grid_origin);
+    fn texture(
+        &mut self,
+        ctx: &egui::Context,
+        key: TextureKey,
+        lod: usize,
+    ) -> Option<egui::TextureId> {
+        if let Some(texture) = self.textures.get_mut(&(key, lod)) {
+            texture.last_used = self.frame;
+            return Some(texture.handle.id());
         }
 
-        // render masked
-        for entry in masked.iter() {
-            self.render_masked_entry(ui, rect, entry, grid_origin);
+        let image = self.decoded_images.get(&key)?;
+
+        if self.uploads_this_frame >= MAX_UPLOADS_PER_FRAME {
+            // try again next frame
+            ctx.request_repaint();
+            return None;
         }
+
+        let (size, rgba) = &image.levels[lod.min(image.levels.len() - 1)];
+
+        let handle = ctx.load_texture(
+            format!("bgst_image-{:?}-{}", key, lod),
+            egui::ColorImage::from_rgba_unmultiplied(*size, rgba),
+            TextureOptions::LINEAR,
+        );
+
+        let id = handle.id();
+
+        self.uploads_this_frame += 1;
+        self.textures.insert(
+            (key, lod),
+            CachedTexture {
+                handle,
+                last_used: self.frame,
+            },
+        );
+
+        Some(id)
     }
 
-    pub fn render_unmasked_entry(
-        &self,
-        ui: &mut egui::Ui,
-        rect: egui::Rect,
-        entry: &BGSTEntry,
-        origin: egui::Vec2,
-    ) {
-        let num_handles = self.decoded_image_handles.len();
+    fn entry_texture_key(&self, entry: &BGSTEntry) -> Option<TextureKey> {
+        if entry.is_masked() {
+            let main = self.image_keys.get(entry.main_image_index as usize)?;
+            let mask = self.image_keys.get(entry.mask_image_index as usize)?;
+            Some(TextureKey::Masked(*main, *mask))
+        } else {
+            let index = std::cmp::max(entry.main_image_index, entry.mask_image_index);
 
-        let index = std::cmp::max(entry.main_image_index, entry.mask_image_index);
+            if index < 0 {
+                return None;
+            }
 
-        if index < 0 || index as usize >= num_handles {
-            return;
+            Some(TextureKey::Image(*self.image_keys.get(index as usize)?))
         }
+    }
 
-        let tex_handle = &self.decoded_image_handles[index as usize];
-        let grid_pos = egui::Vec2::new(entry.grid_x_position as f32, entry.grid_y_position as f32);
+    /// Returns the texture of an entry, with its mask applied if it has one.
+    /// `None` is returned if the image hasn't been decoded yet.
+    pub fn entry_texture(
+        &mut self,
+        ctx: &egui::Context,
+        entry_index: usize,
+        lod: usize,
+    ) -> Option<egui::TextureId> {
+        let entry = *self.bgst_file.as_ref()?.bgst_entries.get(entry_index)?;
+        let key = self.entry_texture_key(&entry)?;
+        self.texture(ctx, key, lod)
+    }
 
-        let image_size_vec = self.tile_size * self.zoom * self.tile_scale;
-        let tile_pos = origin + (grid_pos * image_size_vec);
+    /// Returns the texture of a single image.
+    /// `None` is returned if the image hasn't been decoded yet.
+    pub fn image_texture(
+        &mut self,
+        ctx: &egui::Context,
+        image_index: usize,
+        lod: usize,
+    ) -> Option<egui::TextureId> {
+        let key = TextureKey::Image(*self.image_keys.get(image_index)?);
+        self.texture(ctx, key, lod)
+    }
 
-        let tile_rect = egui::Rect::from_min_size(tile_pos.to_pos2(), image_size_vec);
+    /// The size of one grid cell in world units.
+    pub fn tile_world_size(&self) -> egui::Vec2 {
+        match self.alignment.mode {
+            PlacementMode::Derived => self
+                .bgst_file
+                .as_ref()
+                .map(|f| f.tile_world_size(self.alignment.pixels_per_unit))
+                .unwrap_or_default(),
+
+            PlacementMode::Calibrated => {
+                self.alignment.calibration.tile_size * self.alignment.calibration.scale.get_vec2()
+            }
+        }
+    }
 
-        let painter = ui.painter_at(rect);
+    /// Replaces the calibration with the derived placement, so that calibrating
+    /// starts from a close estimate rather than from scratch.
+    pub fn calibrate_from_file(&mut self) {
+        if let Some(bgst_file) = self.bgst_file.as_ref() {
+            let size = bgst_file.tile_world_size(self.alignment.pixels_per_unit);
+
+            // non-square tiles are expressed as a vertical scale
+            self.alignment.calibration = Calibration {
+                tile_size: size.x,
+                offset: Point2D::default(),
+                scale: Point2D {
+                    x: 1.0,
+                    y: size.y / size.x,
+                },
+            };
+        }
 
-        painter.image(
-            tex_handle.id(),
-            tile_rect,
-            egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::splat(1.0)),
-            egui::Color32::from_rgba_unmultiplied(0xFF, 0xFF, 0xFF, self.opacity),
-        );
+        self.alignment.mode = PlacementMode::Calibrated;
     }
 
-    pub fn render_masked_entry(
+    /// Returns the world-space rectangle covered by an entry as its top-left corner and its size.
+    /// ### Parameters
+    /// - `anchor`: The world position of the bottom-left corner of the grid.
+    /// - `focus`: The world position the camera is looking at, used for parallax.
+    pub fn entry_world_rect(
         &self,
+        entry: &BGSTEntry,
+        anchor: egui::Vec2,
+        focus: egui::Vec2,
+    ) -> (egui::Vec2, egui::Vec2) {
+        let grid_height = self
+            .bgst_file
+            .as_ref()
+            .map(|f| f.grid_height)
+            .unwrap_or_default() as f32;
+
+        let tile_size = self.tile_world_size();
+
+        let offset = match self.alignment.mode {
+            PlacementMode::Derived => egui::Vec2::ZERO,
+            PlacementMode::Calibrated => self.alignment.calibration.offset.get_vec2(),
+        };
+
+        let parallax = self
+            .alignment
+            .parallax
+            .get(entry.layer as usize)
+            .copied()
+            .unwrap_or(1.0);
+
+        // a layer with a factor below 1 lags behind the camera
+        let parallax_shift = (focus - anchor) * (1.0 - parallax);
+
+        // grid rows go downwards, world y goes upwards
+        let top_left = anchor
+            + offset
+            + parallax_shift
+            + egui::Vec2::new(
+                entry.grid_x_position as f32 * tile_size.x,
+                (grid_height - entry.grid_y_position as f32) * tile_size.y,
+            );
+
+        (top_left, tile_size)
+    }
+
+    /// Rendering function for the level editor.
+    /// Each visible layer in `layers` is drawn separately, from back to front.
+    /// Tiles outside of the canvas are skipped.
+    /// ### Parameters
+    /// - `anchor`: The world position of the bottom-left corner of the grid.
+    /// - `layers`: The layers to draw. The level editor draws far layers behind
+    ///   gameplay objects and near layers in front of them.
+    pub fn le_render(
+        &mut self,
         ui: &mut egui::Ui,
         rect: egui::Rect,
-        entry: &BGSTEntry,
-        origin: egui::Vec2,
+        camera: &Camera,
+        anchor: egui::Vec2,
+        layers: std::ops::Range<i16>,
     ) {
-        let painter = ui.painter_at(rect);
+        if self.bgst_file.is_none() {
+            return;
+        }
 
-        let main_index = entry.main_image_index as usize;
-        let mask_index = entry.mask_image_index as usize;
+        let focus = if self.parallax_enabled {
+            camera.convert_from_camera(rect.center() - rect.min)
+        } else {
+            // every layer lines up with the anchor
+            anchor
+        };
 
-        let masked_texture = self.masked_textures.get(&(main_index, mask_index));
-        let grid_pos = egui::Vec2::new(entry.grid_x_position as f32, entry.grid_y_position as f32);
+        let tile_screen_size = self.tile_world_size() * camera.zoom;
+        let lod = self.lod_for_size(tile_screen_size.max_elem());
 
-        let image_size_vec = self.tile_size * self.zoom * self.tile_scale;
+        // upload tiles just outside of the canvas too, so they're ready when panning
+        let cull_rect = rect.expand2(tile_screen_size);
 
-        let tile_pos = origin + (grid_pos * image_size_vec);
+        let painter = ui.painter_at(rect);
+        let tint = egui::Color32::from_rgba_unmultiplied(0xFF, 0xFF, 0xFF, self.opacity);
+
+        for i in 0..self.draw_list.len() {
+            let entry_index = self.draw_list[i];
+            let entry = self.bgst_file.as_ref().unwrap().bgst_entries[entry_index];
+
+            if !layers.contains(&entry.layer)
+                || !self
+                    .visible_layers
+                    .get(entry.layer as usize)
+                    .copied()
+                    .unwrap_or(false)
+            {
+                continue;
+            }
 
-        let tile_rect = egui::Rect::from_min_size(tile_pos.to_pos2(), image_size_vec);
+            let tile_rect = self.entry_screen_rect(rect, camera, &entry, anchor, focus);
 
-        if let Some(tex) = masked_texture {
-            painter.image(
-                tex.id(),
-                tile_rect,
-                egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::splat(1.0)),
-                egui::Color32::from_rgba_unmultiplied(0xFF, 0xFF, 0xFF, self.opacity),
-            );
+            if !cull_rect.intersects(tile_rect) {
+                continue;
+            }
+
+            if let Some(texture) = self.entry_texture(ui.ctx(), entry_index, lod) {
+                painter.image(
+                    texture,
+                    tile_rect,
+                    egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::splat(1.0)),
+                    tint,
+                );
+            }
         }
     }
+
+    fn entry_screen_rect(
+        &self,
+        rect: egui::Rect,
+        camera: &Camera,
+        entry: &BGSTEntry,
+        anchor: egui::Vec2,
+        focus: egui::Vec2,
+    ) -> egui::Rect {
+        let (top_left, size) = self.entry_world_rect(entry, anchor, focus);
+
+        egui::Rect::from_min_size(
+            rect.min + camera.convert_to_camera(top_left),
+            size * camera.zoom,
+        )
+    }
 }
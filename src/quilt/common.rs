@@ -1,6 +1,12 @@
+use anyhow::{Context, Result};
 use byteorder::{BigEndian, ByteOrder};
 use egui::{Pos2, Vec2};
 use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    path::PathBuf,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 use crate::quilt::settings::ZoomType;
 // use encoding_rs::SHIFT_JIS;
@@ -15,7 +21,36 @@ use crate::quilt::settings::ZoomType;
 //     decoded.to_string()
 // }
 
-#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
+/// Returns the path to the `quilt_res` folder next to the executable.
+pub fn quilt_res_path() -> Result<PathBuf> {
+    let current_exe = env::current_exe()?;
+    let current_dir = current_exe
+        .parent()
+        .context("failed to get parent directory")?;
+
+    Ok(current_dir.join("quilt_res"))
+}
+
+/// Identifies an object in the editor, regardless of where it is in its file.
+/// IDs aren't saved; every object gets a new one when it's created or loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId(u32);
+
+impl ObjectId {
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for ObjectId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Point2D {
     pub x: f32,
     pub y: f32,
@@ -60,7 +95,7 @@ impl Point2D {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Point3D {
     pub x: f32,
     pub y: f32,
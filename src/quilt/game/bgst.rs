@@ -12,12 +12,25 @@ const GRID_ENTRY_SIZE: usize = 0x10;
 const COMPRESSED_IMAGE_SIZE: usize = 0x20000;
 const BGST_TILE_SIZE: u32 = 512; // in extra epic yarn, this is 256. todo: check if this can safely be changed
 
+/// The number of image pixels that make up one world unit at a scale modifier of 1.
+/// The game's value for this isn't known. This one was measured on Fountain Gardens by adjusting
+/// it until the edges of the background's tiles lined up with the walls drawn in front of them,
+/// so other backgrounds can need a different value, which is saved with their placement.
+pub const DEFAULT_PIXELS_PER_WORLD_UNIT: f32 = 38.0;
+
 pub const LAYER_NAMES: [&str; 12] = [
     "Far 5", "Far 4", "Far 3", "Far 2", "Far 1", "Map", "Game", "Near 1", "Near 2", "Near 3",
     "Near 4", "Near 5",
 ];
 
-#[derive(Default, Clone, Copy, Debug)]
+/// The first layer that is drawn in front of gameplay objects.
+pub const FIRST_NEAR_LAYER: i16 = 7;
+
+/// How far each layer moves relative to the camera, indexed the same way as `LAYER_NAMES`.
+/// `Map` and `Game` move with the camera; far layers lag behind and near layers move ahead.
+pub const LAYER_PARALLAX: [f32; 12] = [0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.0, 1.1, 1.2, 1.3, 1.4, 1.5];
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
 // all fields here are i16 in the BGST format
 pub struct BGSTEntry {
     pub enabled: bool,
@@ -122,6 +135,12 @@ impl BGSTFile {
         }
     }
 
+    /// The size of one grid cell in world units.
+    pub fn tile_world_size(&self, pixels_per_unit: f32) -> egui::Vec2 {
+        let image_size = egui::Vec2::new(self.image_width as f32, self.image_height as f32);
+        image_size * self.scale_modifier / pixels_per_unit
+    }
+
     pub fn encode(&self) -> Vec<u8> {
         let mut out = Vec::new();
 
@@ -166,8 +185,8 @@ impl BGSTFile {
         out
     }
 
-    fn remove_image_and_references(&mut self, image_index: usize) {
-        self.compressed_images.remove(image_index);
+    fn remove_image_and_references(&mut self, image_index: usize) -> Vec<u8> {
+        let image = self.compressed_images.remove(image_index);
 
         for entry in self.bgst_entries.iter_mut() {
             if entry.main_image_index > image_index as i16 {
@@ -178,16 +197,26 @@ impl BGSTFile {
                 entry.mask_image_index -= 1;
             }
         }
+
+        image
     }
 
-    pub fn remove_entry(&mut self, entry_index: usize) {
+    /// Removes an entry and its images.
+    /// ### Returns
+    /// The removed images and the indices they were removed from, in the order they were removed.
+    pub fn remove_entry(&mut self, entry_index: usize) -> Vec<(usize, Vec<u8>)> {
+        let mut removed = Vec::new();
+
         // remove mask images if present
         if self.bgst_entries[entry_index].is_masked() {
-            self.remove_entry_mask(entry_index);
+            removed.extend(self.remove_entry_mask(entry_index));
         }
 
-        self.remove_image_and_references(self.bgst_entries[entry_index].main_image_index as usize);
+        let main_index = self.bgst_entries[entry_index].main_image_index as usize;
+        removed.push((main_index, self.remove_image_and_references(main_index)));
         self.bgst_entries.remove(entry_index);
+
+        removed
     }
 
     /// Creates a new BGST entry and associated image
@@ -274,8 +303,8 @@ impl BGSTFile {
 
     /// Removes an entry's mask image.
     /// ### Returns
-    /// Whether or not an image was removed.
-    pub fn remove_entry_mask(&mut self, entry_index: usize) -> bool {
+    /// The removed image and its index, if no other entry used it.
+    pub fn remove_entry_mask(&mut self, entry_index: usize) -> Option<(usize, Vec<u8>)> {
         let mask_index = self.bgst_entries[entry_index].mask_image_index;
         self.bgst_entries[entry_index].mask_image_index = -1;
 
@@ -290,11 +319,11 @@ impl BGSTFile {
         if num_users == 0 {
             // we can remove the image
             // account for every entry with a mask index greater than the existing one
-            self.remove_image_and_references(mask_index as usize);
+            let image = self.remove_image_and_references(mask_index as usize);
 
-            true
+            Some((mask_index as usize, image))
         } else {
-            false
+            None
         }
     }
 
@@ -1,19 +1,20 @@
 use crate::quilt::common::*;
 use byteorder::{BigEndian, ByteOrder};
+use serde::{Deserialize, Serialize};
 
 // Unlike the mapbin format, the enbin format is not known in its entirety.
 
 const ENEMY_SIZE: usize = 0x174;
 const ENEMY_PARAMS_SIZE: usize = 0x18;
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnemyParams {
     pub float_params: [f32; 3],
     pub int_params: [i32; 3],
 }
 
 #[allow(non_snake_case)]
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Enemy {
     pub name: String,
     pub behavior: String,
@@ -30,7 +31,10 @@ pub struct Enemy {
     pub unk_16C: u32,
     pub unk_170: u32,
 
+    #[serde(skip)] // editor-only
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
 // #[derive(Default)]
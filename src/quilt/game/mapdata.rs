@@ -1,5 +1,6 @@
 use crate::quilt::common::*;
 use byteorder::{BigEndian, ByteOrder};
+use serde::{Deserialize, Serialize};
 
 const HEADER_SIZE: usize = 0x58;
 const WALL_SIZE: usize = 0x20;
@@ -12,17 +13,20 @@ const ZONE_SIZE: usize = 296;
 const COURSE_INFO_SIZE: usize = 292;
 const BASE_PATH_SIZE: usize = 0x11C;
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Wall {
     pub start: Point2D,
     pub end: Point2D,
     pub _normalized_vector: Point2D, // this field is a bit odd in that x and y are swapped
     pub collision_type: String,
 
+    #[serde(skip)] // editor-only, copied objects get new IDs
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LabeledWall {
     pub start: Point2D,
     pub end: Point2D,
@@ -30,17 +34,20 @@ pub struct LabeledWall {
     pub collision_type: String,
     pub label: String,
 
+    #[serde(skip)]
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Params {
     pub int_params: [i32; 3],
     pub float_params: [f32; 3],
     pub string_params: [String; 3],
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 /// Many fields are unknown.
 pub struct CommonGimmickParams {
     pub common_int_params: [i32; 2],
@@ -51,12 +58,16 @@ pub struct CommonGimmickParams {
     pub string_params: [String; 5],
 }
 
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct CommonGimmick {
     pub hex: String,
     pub position: Point3D,
     pub params: CommonGimmickParams,
 
+    #[serde(skip)]
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
 impl Default for CommonGimmick {
@@ -67,31 +78,38 @@ impl Default for CommonGimmick {
             position: Default::default(),
             params: Default::default(),
             is_selected: Default::default(),
+            id: Default::default(),
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Gimmick {
     pub name: String,
     pub unk_30: [u8; 0x10],
     pub position: Point3D,
     pub params: Params,
 
+    #[serde(skip)]
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Path {
     pub name: String,
     pub path_type: String,
     pub params: Params,
     pub points: Vec<Point2D>,
 
+    #[serde(skip)]
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Zone {
     pub name: String,
     pub unk_20: String,
@@ -99,17 +117,23 @@ pub struct Zone {
     pub bounds_start: Point2D,
     pub bounds_end: Point2D,
 
+    #[serde(skip)]
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CourseInfo {
     pub name: String,
     pub unk_20: String,
     pub params: Params,
     pub position: Point3D,
 
+    #[serde(skip)]
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }
 
 #[derive(Default)]
@@ -278,6 +302,16 @@ impl Mapdata {
         mapdata
     }
 
+    /// Drops collision types that no wall uses anymore.
+    pub fn prune_collision_types(&mut self) {
+        let walls = &self.walls;
+        let labeled_walls = &self.labeled_walls;
+        self.colbin_types.names.retain(|name| {
+            walls.iter().any(|w| &w.collision_type == name)
+                || labeled_walls.iter().any(|w| &w.collision_type == name)
+        });
+    }
+
     pub fn encode(&mut self) -> Vec<u8> {
         // preparations
 
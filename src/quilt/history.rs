@@ -0,0 +1,87 @@
+/// The most steps that can be undone.
+const MAX_HISTORY_LENGTH: usize = 100;
+
+/// The steps of an editor's history that can be undone and redone, oldest first.
+pub struct UndoStack<T> {
+    pub undo_stack: Vec<T>,
+    pub redo_stack: Vec<T>,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+}
+
+impl<T> UndoStack<T> {
+    /// Adds a step. The steps that were undone can't be redone after this,
+    /// and the oldest step is forgotten if there are too many.
+    pub fn push(&mut self, step: T) {
+        self.redo_stack.clear();
+        self.undo_stack.push(step);
+
+        if self.undo_stack.len() > MAX_HISTORY_LENGTH {
+            self.undo_stack.remove(0);
+        }
+    }
+}
+
+/// An editor with an undo history.
+pub trait Undo {
+    type Step;
+
+    fn steps(&mut self) -> &mut UndoStack<Self::Step>;
+
+    /// Records any changes that haven't been recorded yet.
+    fn record_pending(&mut self);
+
+    /// Makes the changes in a step, or reverts them if `reverse` is set.
+    fn apply_step(&mut self, ctx: &egui::Context, step: &Self::Step, reverse: bool);
+
+    /// Whether there's anything open to undo changes to.
+    fn has_history(&self) -> bool;
+
+    fn undo(&mut self, ctx: &egui::Context) {
+        // anything not yet recorded is recorded first, so that it's what gets undone
+        self.record_pending();
+
+        if let Some(step) = self.steps().undo_stack.pop() {
+            self.apply_step(ctx, &step, true);
+            self.steps().redo_stack.push(step);
+        }
+    }
+
+    fn redo(&mut self, ctx: &egui::Context) {
+        self.record_pending();
+
+        if let Some(step) = self.steps().redo_stack.pop() {
+            self.apply_step(ctx, &step, false);
+            self.steps().undo_stack.push(step);
+        }
+    }
+
+    /// Handles Ctrl+Z, Ctrl+Y and Ctrl+Shift+Z.
+    /// Text fields handle these themselves while they're being typed into.
+    fn handle_history_shortcuts(&mut self, ctx: &egui::Context) {
+        if !self.has_history() || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+        let redo_alt = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        );
+
+        // the more specific shortcut has to be checked first
+        if ctx.input_mut(|i| i.consume_shortcut(&redo_alt) || i.consume_shortcut(&redo)) {
+            self.redo(ctx);
+        } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+            self.undo(ctx);
+        }
+    }
+}
@@ -1,33 +1,55 @@
+mod le_bgst;
+mod le_bounds;
+mod le_brush;
 mod le_canvas;
+mod le_clipboard;
+mod le_collision;
+mod le_enemy;
+mod le_history;
 mod le_io;
+mod le_labels;
+mod le_lint;
+mod le_normals;
 mod le_object;
+mod le_path;
+mod le_pen;
+mod le_prefab;
+mod le_references;
+mod le_selection;
+mod le_snap;
+mod le_topology;
 mod le_util;
+mod le_zone;
 use anyhow::Context;
 
-use egui::{self, Button, DragValue, TextureHandle};
+use egui::{self, Button, TextureHandle};
 use std::{collections::HashMap, env, fs, path::PathBuf};
 // use egui::{self, Button, TextureHandle};
-use super::{bgst_renderer::BGSTRenderer, common::Camera};
+use super::{
+    bgst_renderer::BGSTRenderer,
+    common::{Camera, ObjectId},
+};
 // use super::common::Camera;
 
 use crate::quilt::{
     game::{endata::*, mapdata::*},
+    history::Undo,
     settings::*,
     util::comment::*,
 };
 
-#[derive(PartialEq)]
-// These are indices
-enum ObjectIndex {
-    Wall(usize),
-    LabeledWall(usize),
-    CommonGimmick(usize),
-    Gimmick(usize),
-    Path(usize),
-    Zone(usize),
-    CourseInfo(usize),
-    Enemy(usize),
-    Comment(usize),
+/// Refers to an object by its ID, so that it stays valid when other objects are added or removed.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+enum ObjectRef {
+    Wall(ObjectId),
+    LabeledWall(ObjectId),
+    CommonGimmick(ObjectId),
+    Gimmick(ObjectId),
+    Path(ObjectId),
+    Zone(ObjectId),
+    CourseInfo(ObjectId),
+    Enemy(ObjectId),
+    Comment(ObjectId),
 }
 
 #[derive(PartialEq)]
@@ -41,6 +63,8 @@ enum ObjectType {
     CourseInfo,
     Enemy,
     Comment,
+    /// A prefab from the prefab library, by name.
+    Prefab(String),
 }
 
 #[derive(PartialEq, Clone, Copy, Default)]
@@ -69,8 +93,21 @@ pub struct LevelEditor {
     // editor
     display_none: bool,
     camera: Camera,
-    selected_object_indices: Vec<ObjectIndex>,
+    selected_objects: Vec<ObjectRef>,
+    /// The corners of the selection box being dragged, in world units.
+    selection_box: Option<(egui::Vec2, egui::Vec2)>,
+    history: le_history::History,
     current_add_object: Option<ObjectType>,
+    prefabs: le_prefab::PrefabLibrary,
+    wall_pen: le_pen::WallPen,
+    path_tools: le_path::PathTools,
+    /// A renamed path whose old name is still used by enemies.
+    path_rename: Option<le_enemy::PathRename>,
+    collision_brush: le_brush::CollisionBrush,
+    topology: le_topology::TopologyReport,
+    lint: le_lint::LintReport,
+    bounds: le_bounds::LevelBounds,
+    label_manager: le_labels::LabelManager,
     object_data_json: Option<serde_json::Value>,
     is_object_data_valid: bool,
 
@@ -84,6 +121,11 @@ pub struct LevelEditor {
     course_info_edit_mode: EditMode,
     comment_edit_mode: EditMode,
     show_canvas_options: bool,
+    show_wall_normals: bool,
+    /// Draws every reference between objects, rather than only those of the selected objects.
+    show_references: bool,
+    collision_styles: le_collision::CollisionStyles,
+    snap: le_snap::SnapSettings,
 
     // ui
     show_object_context_menu: bool,
@@ -92,7 +134,20 @@ pub struct LevelEditor {
     // graphics
     object_textures: HashMap<String, TextureHandle>,
     render_bgst: bool,
+    show_bgst_options: bool,
     bgst_renderer: BGSTRenderer,
+    bgst_path: Option<PathBuf>,
+    /// The background saved for the current level if it couldn't be loaded,
+    /// so that it isn't forgotten when the settings are saved again.
+    missing_bgst_path: Option<PathBuf>,
+    /// The background settings as they were last saved, to tell when they change.
+    saved_bgst: le_bgst::LevelBGST,
+    /// The game dump that was searched for backgrounds, and the backgrounds found in it.
+    bgst_index: Option<(PathBuf, Vec<PathBuf>)>,
+    /// A search of the game dump running in the background.
+    bgst_search: Option<le_bgst::BGSTSearch>,
+    /// A background found for the current level that the user hasn't loaded yet.
+    bgst_offer: Option<PathBuf>,
 }
 
 impl LevelEditor {
@@ -130,6 +185,9 @@ impl LevelEditor {
             self.camera.zoom_type = settings.zoom_type.clone();
         }
 
+        self.bgst_renderer.poll(ui.ctx());
+        self.handle_history_shortcuts(ui.ctx());
+
         egui::TopBottomPanel::top("le_top_panel")
         .show(ui.ctx(), |ui|{
             egui::menu::bar(ui, |ui|{
@@ -147,6 +205,8 @@ impl LevelEditor {
                             self.snap_to_start();
                         }
 
+                        self.discover_bgst(settings);
+
                         ui.close_menu();
                     }
 
@@ -157,36 +217,98 @@ impl LevelEditor {
                             self.snap_to_start();
                         }
 
+                        self.discover_bgst(settings);
+
                         ui.close_menu();
                     }
 
                     if ui.add_enabled(self.file_open && self.file_path.is_some(), Button::new("Save Archive"))
                     .clicked() {
-                        let _ = self.save_file(false);
+                        self.request_save(le_lint::SaveKind::Archive { save_as: false });
                         ui.close_menu();
                     }
 
                     if ui.add_enabled(self.file_open, Button::new("Save Archive as"))
                     .clicked() {
-                        let _ = self.save_file(true);
+                        self.request_save(le_lint::SaveKind::Archive { save_as: true });
                         ui.close_menu();
                     }
 
                     if ui.add_enabled(self.file_open && self.file_path.is_some(), Button::new("Save Folder"))
                     .clicked() {
-                        let _ = self.save_folder(false);
+                        self.request_save(le_lint::SaveKind::Folder { save_as: false });
                         ui.close_menu();
                     }
 
                     if ui.add_enabled(self.file_open, Button::new("Save Folder as"))
                     .clicked() {
-                        let _ = self.save_folder(true);
+                        self.request_save(le_lint::SaveKind::Folder { save_as: true });
                         ui.close_menu();
                     }
 
                     if ui.add_enabled(self.file_open, Button::new("Open BGST"))
                     .clicked() {
-                        let _ = self.bgst_renderer.open_file(ui);
+                        if let Ok(path) = self.bgst_renderer.open_file() {
+                            self.bgst_path = Some(path);
+                        }
+
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Edit", |ui|{
+                    if ui.add_enabled(!self.history.steps.undo_stack.is_empty(), Button::new("Undo").shortcut_text("Ctrl+Z"))
+                    .clicked() {
+                        self.undo(ui.ctx());
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(!self.history.steps.redo_stack.is_empty(), Button::new("Redo").shortcut_text("Ctrl+Y"))
+                    .clicked() {
+                        self.redo(ui.ctx());
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(self.file_open, Button::new("History")).clicked() {
+                        self.history.show_panel = !self.history.show_panel;
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
+                    if ui.add_enabled(self.has_selected_walls(), Button::new("Flip Wall Direction"))
+                    .on_hover_text("Swaps the start and end of the selected walls, which flips the side they're solid on.")
+                    .clicked() {
+                        self.flip_selected_walls();
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(self.has_selected_walls(), Button::new("Make Chain Consistent"))
+                    .on_hover_text("Flips the walls connected to the selected walls so that they all face the same way.")
+                    .clicked() {
+                        self.make_chains_consistent();
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(self.file_open, Button::new("Check Collision")).clicked() {
+                        self.topology.toggle_panel();
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(self.file_open, Button::new("Level Properties")).clicked() {
+                        self.bounds.show_panel = !self.bounds.show_panel;
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(self.file_open, Button::new("Check Level"))
+                    .on_hover_text("Lists problems that would stop the level saving or working in game. Levels are also checked when they're saved.")
+                    .clicked() {
+                        self.lint.toggle_panel();
+                        ui.close_menu();
+                    }
+
+                    if ui.add_enabled(self.file_open, Button::new("Wall Labels")).clicked() {
+                        self.label_manager.show_panel = !self.label_manager.show_panel;
                         ui.close_menu();
                     }
                 });
@@ -221,46 +343,94 @@ impl LevelEditor {
 
                 if bgst_valid {
                     ui.checkbox(&mut self.render_bgst, "Display background?")
-                    .on_hover_text("This rendering is by no means perfect; it is a best estimate. Use the values on the right to change render settings.");
-
-                    ui.label("Tile size");
-                    ui.add(
-                        DragValue::new(&mut self.bgst_renderer.tile_size).speed(0.1)
-                    );
-
-                    ui.label("Tile X offset");
-                    ui.add(
-                        DragValue::new(&mut self.bgst_renderer.tile_offset.x).speed(0.1)
-                    );
-
-                    ui.label("Tile Y offset");
-                    ui.add(
-                        DragValue::new(&mut self.bgst_renderer.tile_offset.y).speed(0.1)
-                    );
-
-                    ui.label("Tile X scale");
-                    ui.add(
-                        DragValue::new(&mut self.bgst_renderer.tile_scale.x).speed(0.1)
-                    );
-
-                    ui.label("Tile Y scale");
-                    ui.add(
-                        DragValue::new(&mut self.bgst_renderer.tile_scale.y).speed(0.1)
-                    );
-
-                    ui.label("Tile opacity");
-                    ui.add(
-                        DragValue::new(&mut self.bgst_renderer.opacity).speed(1).range(u8::MIN..=u8::MAX)
-                    );
+                    .on_hover_text("The background is placed using the values in the BGST file. If it doesn't line up, calibrate it in the background options.");
+
+                    if ui.button("Background Options").clicked() {
+                        self.show_bgst_options = !self.show_bgst_options;
+                    }
+
+                    if let Some(progress) = self.bgst_renderer.decode_progress() {
+                        ui.add(egui::ProgressBar::new(progress)
+                            .desired_width(200.0)
+                            .text("Decoding background"));
+                    }
+                } else {
+                    self.show_bgst_options = false;
                 }
             });
         });
 
+        if self.show_bgst_options {
+            egui::Window::new("Background Options")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_bgst_options_ui(ui);
+                });
+        }
+
+        self.poll_bgst_search(ui.ctx());
+        self.show_bgst_offer_ui(ui.ctx());
+        self.autosave_bgst_settings(ui.ctx());
+
+        if self.prefabs.show_panel && self.file_open {
+            egui::Window::new("Prefabs")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_prefab_ui(ui);
+                });
+        }
+
+        if self.topology.show_panel && self.file_open {
+            egui::Window::new("Collision Check")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_topology_ui(ui);
+                });
+        }
+
+        if self.bounds.show_panel && self.file_open {
+            egui::Window::new("Level Properties")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_level_properties_ui(ui);
+                });
+        }
+
+        if self.lint.show_panel && self.file_open {
+            egui::Window::new("Level Check")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_lint_ui(ui);
+                });
+        }
+
+        if self.label_manager.show_panel && self.file_open {
+            egui::Window::new("Wall Labels")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_label_manager_ui(ui);
+                });
+        }
+
+        if self.file_open {
+            self.show_path_rename_ui(ui.ctx());
+        }
+
+        if self.history.show_panel && self.file_open {
+            egui::Window::new("History")
+                .collapsible(true)
+                .show(ui.ctx(), |ui| {
+                    self.show_history_ui(ui);
+                });
+        }
+
         egui::CentralPanel::default().show(ui.ctx(), |ui| {
             if self.file_open {
                 self.show_editor_ui(ui);
             }
         });
+
+        self.update_history(ui.ctx());
     }
 
     fn add_object_context_menu(&mut self, ui: &mut egui::Ui) {
@@ -280,6 +450,24 @@ impl LevelEditor {
                             self.current_add_object = Some(ObjectType::LabeledWall);
                         }
 
+                        if ui
+                            .button("Draw Walls")
+                            .on_hover_text("Draw a chain of connected walls")
+                            .clicked()
+                        {
+                            self.start_wall_pen(false);
+                            self.show_object_context_menu = false;
+                        }
+
+                        if ui
+                            .button("Draw Labeled Walls")
+                            .on_hover_text("Draw a chain of connected labeled walls")
+                            .clicked()
+                        {
+                            self.start_wall_pen(true);
+                            self.show_object_context_menu = false;
+                        }
+
                         ui.collapsing("Add Common Gimmick", |ui| {
                             if ui
                                 .button("New")
@@ -372,7 +560,15 @@ impl LevelEditor {
             self.add_object_context_menu(ui);
         }
 
-        if response.dragged_by(egui::PointerButton::Primary) {
+        self.handle_clipboard_shortcuts(ui, response);
+
+        let is_box_selecting = self.handle_selection_box(ui, response);
+
+        // dragging paints walls while the brush is active
+        if response.dragged_by(egui::PointerButton::Primary)
+            && !is_box_selecting
+            && !self.collision_brush.active
+        {
             let delta = response.drag_delta();
             self.camera.pan(delta / self.camera.zoom);
         }
@@ -383,44 +579,64 @@ impl LevelEditor {
     }
 
     fn process_object_attributes(&mut self, ui: &mut egui::Ui) {
-        if self.selected_object_indices.len() != 1 {
+        // objects may have been removed, e.g. by undoing their creation
+        let selected_objects = std::mem::take(&mut self.selected_objects);
+        self.selected_objects = selected_objects
+            .into_iter()
+            .filter(|o| self.object_index(*o).is_some())
+            .collect();
+
+        if self.selected_objects.len() > 1 {
+            self.process_multi_object_attributes(ui);
+            return;
+        }
+
+        if self.selected_objects.len() != 1 {
             return;
         }
 
-        match self.selected_object_indices[0] {
-            ObjectIndex::Wall(index) => {
+        let object = self.selected_objects[0];
+
+        let Some(index) = self.object_index(object) else {
+            return;
+        };
+
+        self.show_reference_ui(ui.ctx());
+
+        match object {
+            ObjectRef::Wall(_) => {
                 self.process_wall_attributes(ui, index);
             }
 
-            ObjectIndex::LabeledWall(index) => {
+            ObjectRef::LabeledWall(_) => {
                 self.process_labeled_wall_attributes(ui, index);
             }
 
-            ObjectIndex::CommonGimmick(index) => {
+            ObjectRef::CommonGimmick(_) => {
                 let _ = self.process_common_gimmick_attributes(ui, index);
             }
 
-            ObjectIndex::Gimmick(index) => {
+            ObjectRef::Gimmick(_) => {
                 self.process_gimmick_attributes(ui, index);
             }
 
-            ObjectIndex::Path(index) => {
+            ObjectRef::Path(_) => {
                 self.process_path_attributes(ui, index);
             }
 
-            ObjectIndex::Zone(index) => {
+            ObjectRef::Zone(_) => {
                 self.process_zone_attributes(ui, index);
             }
 
-            ObjectIndex::CourseInfo(index) => {
+            ObjectRef::CourseInfo(_) => {
                 self.process_course_info_attributes(ui, index);
             }
 
-            ObjectIndex::Enemy(index) => {
+            ObjectRef::Enemy(_) => {
                 self.process_enemy_attributes(ui, index);
             }
 
-            ObjectIndex::Comment(index) => {
+            ObjectRef::Comment(_) => {
                 self.process_comment_attributes(ui, index);
             }
         }
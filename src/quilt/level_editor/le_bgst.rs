@@ -0,0 +1,477 @@
+use super::{LevelEditor, le_object};
+use crate::quilt::{
+    bgst_renderer::{BGSTAlignment, PlacementMode},
+    common::quilt_res_path,
+    game::bgst::{LAYER_NAMES, LAYER_PARALLAX},
+    settings::LevelEditorSettings,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, TryRecvError},
+    thread,
+};
+
+const ALIGNMENT_FILE_NAME: &str = "bgst_alignment.json";
+
+/// The background settings remembered for a level.
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelBGST {
+    /// The `.bgst3` file shown behind the level.
+    #[serde(default)]
+    pub bgst_path: Option<PathBuf>,
+    #[serde(default = "LevelBGST::default_opacity")]
+    pub opacity: u8,
+    #[serde(flatten)]
+    pub alignment: BGSTAlignment,
+}
+
+impl LevelBGST {
+    fn default_opacity() -> u8 {
+        128
+    }
+}
+
+/// Finds every `.bgst3` file in a folder and its subfolders.
+/// Folders that can't be read are skipped, and symlinked folders aren't followed,
+/// so that a link back up the tree doesn't make the search run forever.
+fn find_bgst_files(root: &Path) -> Result<Vec<PathBuf>> {
+    // a root that can't be read means the game dump setting is wrong
+    fs::read_dir(root)?;
+
+    let mut files = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        let read_dir = match fs::read_dir(&folder) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                eprintln!("Failed to search {:?}. Reason: {:?}", folder, e);
+                continue;
+            }
+        };
+
+        for entry in read_dir {
+            let (path, file_type) = match entry.and_then(|e| Ok((e.path(), e.file_type()?))) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    eprintln!("Failed to read a folder entry. Reason: {:?}", e);
+                    continue;
+                }
+            };
+
+            if file_type.is_dir() {
+                folders.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "bgst3") {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// A search of a game dump for backgrounds, running on another thread.
+pub struct BGSTSearch {
+    root: PathBuf,
+    receiver: mpsc::Receiver<Result<Vec<PathBuf>>>,
+}
+
+impl BGSTSearch {
+    fn start(root: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let search_root = root.clone();
+
+        thread::spawn(move || {
+            let _ = sender.send(find_bgst_files(&search_root));
+        });
+
+        Self { root, receiver }
+    }
+}
+
+/// The part of a file name before any extension, in lowercase.
+fn base_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    let base = name.split('.').next()?.to_string();
+
+    (!base.is_empty()).then_some(base)
+}
+
+impl LevelEditor {
+    /// The name per-level data is stored under, i.e. the name of the archive or folder.
+    pub fn level_key(&self) -> Option<String> {
+        self.file_path
+            .as_ref()?
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }
+
+    fn read_level_bgsts() -> Result<BTreeMap<String, LevelBGST>> {
+        let file_path = quilt_res_path()?.join(ALIGNMENT_FILE_NAME);
+
+        if !fs::exists(&file_path)? {
+            return Ok(BTreeMap::new());
+        }
+
+        let contents = fs::read_to_string(file_path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The world position of the bottom-left corner of the BGST grid,
+    /// which is the bottom-left of the `BG_BASE` gimmick's rendering square.
+    pub fn bgst_anchor(&self) -> Option<egui::Vec2> {
+        let bg_base = self
+            .current_mapdata
+            .gimmicks
+            .iter()
+            .find(|g| &g.name == "BG_BASE")?;
+
+        Some(egui::Vec2::new(
+            bg_base.position.x - le_object::SQUARE_SIZE / 2.0,
+            bg_base.position.y,
+        ))
+    }
+
+    fn current_level_bgst(&self) -> LevelBGST {
+        LevelBGST {
+            bgst_path: self
+                .bgst_path
+                .clone()
+                .or_else(|| self.missing_bgst_path.clone()),
+            opacity: self.bgst_renderer.opacity,
+            alignment: self.bgst_renderer.alignment,
+        }
+    }
+
+    /// Restores the background saved for the current level, loading its BGST if it still exists.
+    /// The default alignment is used if nothing was saved.
+    pub fn load_bgst_settings(&mut self) {
+        let saved = self
+            .level_key()
+            .and_then(|key| Self::read_level_bgsts().ok()?.remove(&key));
+
+        let level_bgst = saved.clone().unwrap_or_else(|| LevelBGST {
+            opacity: LevelBGST::default_opacity(),
+            ..Default::default()
+        });
+
+        self.bgst_renderer.alignment = level_bgst.alignment;
+        self.bgst_renderer.opacity = level_bgst.opacity;
+        self.bgst_path = None;
+        self.missing_bgst_path = None;
+
+        if let Some(path) = level_bgst.bgst_path.as_ref() {
+            match self.bgst_renderer.load_file(path) {
+                Ok(()) => {
+                    self.bgst_path = Some(path.clone());
+                    self.render_bgst = true;
+                }
+
+                Err(e) => {
+                    eprintln!("Failed to load BGST {:?}. Reason: {:?}", path, e);
+
+                    // a missing file is kept in the saved settings until another one is chosen
+                    self.missing_bgst_path = Some(path.clone());
+                }
+            }
+        }
+
+        self.saved_bgst = saved.unwrap_or_else(|| self.current_level_bgst());
+    }
+
+    /// Saves the current background settings for the current level.
+    pub fn save_bgst_settings(&mut self) -> Result<()> {
+        let key = self.level_key().context("the level has not been saved")?;
+        let level_bgst = self.current_level_bgst();
+
+        let mut level_bgsts = Self::read_level_bgsts()?;
+        level_bgsts.insert(key, level_bgst.clone());
+
+        let quilt_res = quilt_res_path()?;
+
+        if !fs::exists(&quilt_res)? {
+            fs::create_dir(&quilt_res)?;
+        }
+
+        let contents = serde_json::to_string_pretty(&level_bgsts)?;
+        fs::write(quilt_res.join(ALIGNMENT_FILE_NAME), contents)?;
+
+        self.saved_bgst = level_bgst;
+
+        Ok(())
+    }
+
+    /// Saves the background settings if they changed, waiting until nothing is being dragged.
+    pub fn autosave_bgst_settings(&mut self, ctx: &egui::Context) {
+        if self.level_key().is_none()
+            || ctx.input(|i| i.pointer.any_down())
+            || self.current_level_bgst() == self.saved_bgst
+        {
+            return;
+        }
+
+        if let Err(e) = self.save_bgst_settings() {
+            eprintln!("Failed to save BGST settings. Reason: {:?}", e);
+
+            // don't retry every frame
+            self.saved_bgst = self.current_level_bgst();
+        }
+    }
+
+    /// Names the current level's background may be stored under, best match first.
+    /// `BG_BASE` names its background in its string parameters; otherwise the
+    /// background usually shares its name with the level archive.
+    fn bgst_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .current_mapdata
+            .gimmicks
+            .iter()
+            .filter(|g| &g.name == "BG_BASE")
+            .flat_map(|g| g.params.string_params.iter())
+            .filter_map(|param| base_name(Path::new(param)))
+            .collect();
+
+        if let Some(name) = self.file_path.as_deref().and_then(base_name) {
+            names.push(name);
+        }
+
+        names
+    }
+
+    /// Looks for the current level's background in the game dump, and offers to load it if found.
+    /// Nothing is done if a background was already restored for the level.
+    pub fn discover_bgst(&mut self, settings: &LevelEditorSettings) {
+        self.bgst_offer = None;
+
+        if !self.file_open || self.bgst_path.is_some() {
+            return;
+        }
+
+        let Some(root) = settings.game_root.as_ref() else {
+            return;
+        };
+
+        // searching a whole dump takes a moment, so the results are kept
+        if self.bgst_index.as_ref().is_some_and(|(r, _)| r == root) {
+            self.offer_bgst();
+        } else if self.bgst_search.as_ref().is_none_or(|s| &s.root != root) {
+            // the offer is made once the search finishes
+            self.bgst_search = Some(BGSTSearch::start(root.clone()));
+        }
+    }
+
+    /// Receives the results of a search of the game dump, if one has finished.
+    /// This should be called once per frame.
+    pub fn poll_bgst_search(&mut self, ctx: &egui::Context) {
+        let Some(search) = self.bgst_search.as_ref() else {
+            return;
+        };
+
+        let result = match search.receiver.try_recv() {
+            Ok(result) => result,
+
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint();
+                return;
+            }
+
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("the search stopped")),
+        };
+
+        let Some(search) = self.bgst_search.take() else {
+            return;
+        };
+
+        match result {
+            Ok(files) => {
+                self.bgst_index = Some((search.root, files));
+
+                if self.file_open && self.bgst_path.is_none() {
+                    self.offer_bgst();
+                }
+            }
+
+            Err(e) => eprintln!("Failed to search the game dump. Reason: {:?}", e),
+        }
+    }
+
+    /// Offers the background in the searched game dump with the same name as the current level.
+    fn offer_bgst(&mut self) {
+        let Some((_, files)) = self.bgst_index.as_ref() else {
+            return;
+        };
+
+        let names = self.bgst_names();
+
+        self.bgst_offer = names
+            .iter()
+            .find_map(|name| {
+                files
+                    .iter()
+                    .find(|file| base_name(file).as_ref() == Some(name))
+            })
+            .cloned();
+    }
+
+    /// Asks whether a discovered background should be loaded.
+    pub fn show_bgst_offer_ui(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.bgst_offer.clone() else {
+            return;
+        };
+
+        egui::Window::new("Background Found")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("A background was found for this level. Load it?");
+                ui.weak(path.display().to_string());
+
+                ui.horizontal(|ui| {
+                    if ui.button("Load").clicked() {
+                        match self.bgst_renderer.load_file(&path) {
+                            Ok(()) => {
+                                self.bgst_path = Some(path.clone());
+                                self.render_bgst = true;
+                            }
+
+                            Err(e) => eprintln!("Failed to load BGST {:?}. Reason: {:?}", path, e),
+                        }
+
+                        self.bgst_offer = None;
+                    }
+
+                    if ui.button("Not now").clicked() {
+                        self.bgst_offer = None;
+                    }
+                });
+            });
+    }
+
+    pub fn show_bgst_options_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Opacity");
+            ui.add(
+                egui::DragValue::new(&mut self.bgst_renderer.opacity)
+                    .speed(1)
+                    .range(u8::MIN..=u8::MAX),
+            );
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Placement");
+            ui.selectable_value(
+                &mut self.bgst_renderer.alignment.mode,
+                PlacementMode::Derived,
+                "From file",
+            )
+            .on_hover_text("Place the background using the values in the BGST file.");
+
+            ui.selectable_value(
+                &mut self.bgst_renderer.alignment.mode,
+                PlacementMode::Calibrated,
+                "Calibrated",
+            )
+            .on_hover_text("Place the background using manually entered values.");
+        });
+
+        match self.bgst_renderer.alignment.mode {
+            PlacementMode::Derived => {
+                ui.horizontal(|ui| {
+                    ui.label("Pixels per unit");
+                    ui.add(
+                        egui::DragValue::new(&mut self.bgst_renderer.alignment.pixels_per_unit)
+                            .speed(0.1)
+                            .range(1.0..=1000.0),
+                    )
+                    .on_hover_text("How many pixels of the background make up one world unit.");
+                });
+
+                let tile_size = self.bgst_renderer.tile_world_size();
+                ui.label(format!(
+                    "Tile size: {:.3} × {:.3}",
+                    tile_size.x, tile_size.y
+                ));
+
+                if ui
+                    .button("Calibrate")
+                    .on_hover_text("Start calibrating from the values in the BGST file.")
+                    .clicked()
+                {
+                    self.bgst_renderer.calibrate_from_file();
+                }
+            }
+
+            PlacementMode::Calibrated => {
+                let calibration = &mut self.bgst_renderer.alignment.calibration;
+
+                egui::Grid::new("le_bgst_calibration").show(ui, |ui| {
+                    ui.label("Tile size");
+                    ui.add(egui::DragValue::new(&mut calibration.tile_size).speed(0.01));
+                    ui.end_row();
+
+                    ui.label("Offset");
+                    ui.add(egui::DragValue::new(&mut calibration.offset.x).speed(0.1));
+                    ui.add(egui::DragValue::new(&mut calibration.offset.y).speed(0.1));
+                    ui.end_row();
+
+                    ui.label("Scale");
+                    ui.add(egui::DragValue::new(&mut calibration.scale.x).speed(0.001));
+                    ui.add(egui::DragValue::new(&mut calibration.scale.y).speed(0.001));
+                    ui.end_row();
+                });
+
+                if ui.button("Reset to file values").clicked() {
+                    self.bgst_renderer.calibrate_from_file();
+                }
+            }
+        }
+
+        ui.collapsing("Layers", |ui| {
+            ui.checkbox(&mut self.bgst_renderer.parallax_enabled, "Parallax")
+                .on_hover_text(
+                    "Far and near layers move relative to the camera, as they do in-game.",
+                );
+
+            egui::Grid::new("le_bgst_layers").show(ui, |ui| {
+                ui.label("Layer");
+                ui.label("Parallax factor");
+                ui.end_row();
+
+                for (i, name) in LAYER_NAMES.iter().enumerate() {
+                    ui.checkbox(&mut self.bgst_renderer.visible_layers[i], *name);
+                    ui.add(
+                        egui::DragValue::new(&mut self.bgst_renderer.alignment.parallax[i])
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX),
+                    );
+                    ui.end_row();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Show all").clicked() {
+                    self.bgst_renderer.visible_layers = [true; 12];
+                }
+
+                if ui.button("Reset parallax").clicked() {
+                    self.bgst_renderer.alignment.parallax = LAYER_PARALLAX;
+                }
+            });
+        });
+
+        if self.level_key().is_some() {
+            ui.separator();
+            ui.label("These settings are remembered for this level.");
+        }
+    }
+}
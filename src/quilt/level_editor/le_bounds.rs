@@ -0,0 +1,136 @@
+use super::{LevelEditor, le_zone::process_resize_handles};
+
+use crate::quilt::common::Point2D;
+
+use egui::{Rect, Vec2};
+
+const BOUNDS_COLOR: egui::Color32 = egui::Color32::from_rgb(0xFF, 0x8C, 0x00);
+
+/// The level's bounds, which the game uses to limit the camera and despawn objects.
+pub struct LevelBounds {
+    pub show_panel: bool,
+    pub visible: bool,
+    /// The bounds can be resized on the canvas.
+    editing: bool,
+    /// How far past the walls and objects the bounds are placed when fitted, in world units.
+    padding: f32,
+}
+
+impl Default for LevelBounds {
+    fn default() -> Self {
+        Self {
+            show_panel: false,
+            visible: true,
+            editing: false,
+            padding: 2.0,
+        }
+    }
+}
+
+impl LevelEditor {
+    /// Fits the bounds around every wall and object, plus the padding.
+    fn fit_bounds_to_contents(&mut self) {
+        let points: Vec<Vec2> = self
+            .object_points()
+            .into_iter()
+            .flat_map(|(_, points)| points)
+            .collect();
+
+        let Some(first) = points.first() else {
+            return;
+        };
+
+        let (min, max) = points
+            .iter()
+            .fold((*first, *first), |(min, max), p| (min.min(*p), max.max(*p)));
+
+        let padding = Vec2::splat(self.bounds.padding);
+
+        self.history.label_next("Fit Bounds");
+        self.current_mapdata.bounds_min = Point2D::from_vec2(min - padding);
+        self.current_mapdata.bounds_max = Point2D::from_vec2(max + padding);
+    }
+
+    pub fn show_level_properties_ui(&mut self, ui: &mut egui::Ui) {
+        let mapdata = &mut self.current_mapdata;
+
+        egui::Grid::new("le_level_bounds_grid").show(ui, |ui| {
+            for (label, point) in [
+                ("Bounds min", &mut mapdata.bounds_min),
+                ("Bounds max", &mut mapdata.bounds_max),
+            ] {
+                ui.label(label);
+                ui.add(egui::DragValue::new(&mut point.x).speed(0.1).prefix("X: "));
+                ui.add(egui::DragValue::new(&mut point.y).speed(0.1).prefix("Y: "));
+                ui.end_row();
+            }
+        });
+
+        let size = mapdata.bounds_max.get_vec2() - mapdata.bounds_min.get_vec2();
+        ui.label(format!("Size: {:.2} × {:.2}", size.x.abs(), size.y.abs()));
+
+        ui.separator();
+
+        ui.checkbox(&mut self.bounds.visible, "Show bounds");
+
+        ui.add_enabled(
+            self.bounds.visible,
+            egui::Checkbox::new(&mut self.bounds.editing, "Resize on canvas"),
+        )
+        .on_hover_text("Shows handles on the edges of the bounds that resize them when dragged.");
+
+        ui.horizontal(|ui| {
+            if ui
+                .button("Fit to contents")
+                .on_hover_text("Fits the bounds around every wall and object.")
+                .clicked()
+            {
+                self.fit_bounds_to_contents();
+            }
+
+            ui.label("Padding");
+            ui.add(
+                egui::DragValue::new(&mut self.bounds.padding)
+                    .speed(0.1)
+                    .range(0.0..=100.0),
+            );
+        });
+    }
+
+    /// Draws the level's bounds, with handles that resize them if they're being edited.
+    pub(super) fn process_level_bounds(&mut self, ui: &egui::Ui, rect: Rect) {
+        if !self.bounds.visible {
+            return;
+        }
+
+        let mapdata = &mut self.current_mapdata;
+        let (start, end) = (
+            rect.min + self.camera.convert_to_camera(mapdata.bounds_min.get_vec2()),
+            rect.min + self.camera.convert_to_camera(mapdata.bounds_max.get_vec2()),
+        );
+
+        let painter = ui.painter_at(rect);
+        let bounds = Rect::from_two_pos(start, end);
+
+        painter.rect_stroke(bounds, 0.0, egui::Stroke::new(1.5_f32, BOUNDS_COLOR));
+        painter.text(
+            bounds.left_top() + Vec2::new(0.0, -2.0),
+            egui::Align2::LEFT_BOTTOM,
+            "Level bounds",
+            egui::FontId::proportional(12.0),
+            BOUNDS_COLOR,
+        );
+
+        if self.bounds.editing {
+            process_resize_handles(
+                ui,
+                rect,
+                &self.camera,
+                &self.snap,
+                egui::Id::new("le_level_bounds"),
+                [&mut mapdata.bounds_min, &mut mapdata.bounds_max],
+                BOUNDS_COLOR,
+            );
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use super::{COLLISION_TYPES, LevelEditor};
+
+use egui::{Pos2, Rect, Vec2};
+
+/// How close the mouse has to be to a wall to paint it, in pixels.
+const BRUSH_RADIUS: f32 = 6.0;
+
+/// Assigns a collision type to every wall the mouse is clicked or dragged across.
+pub struct CollisionBrush {
+    pub active: bool,
+    collision_type: String,
+}
+
+impl Default for CollisionBrush {
+    fn default() -> Self {
+        Self {
+            active: false,
+            collision_type: String::from("NML"),
+        }
+    }
+}
+
+/// The distance from `point` to the segment from `start` to `end`.
+fn distance_to_segment(point: Pos2, start: Pos2, end: Pos2) -> f32 {
+    let direction = end - start;
+
+    if direction.length_sq() == 0.0 {
+        return (point - start).length();
+    }
+
+    let t = ((point - start).dot(direction) / direction.length_sq()).clamp(0.0, 1.0);
+    (point - (start + direction * t)).length()
+}
+
+impl LevelEditor {
+    pub(super) fn toggle_collision_brush(&mut self) {
+        self.collision_brush.active = !self.collision_brush.active;
+
+        if self.collision_brush.active {
+            self.current_add_object = None;
+            self.wall_pen.active = false;
+        }
+    }
+
+    /// Paints the walls under the mouse while the primary button is held.
+    pub(super) fn process_collision_brush(&mut self, ui: &egui::Ui, rect: Rect) {
+        let ctx = ui.ctx();
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.collision_brush.active = false;
+            return;
+        }
+
+        self.show_collision_brush_ui(ctx);
+
+        if !self.collision_brush.active {
+            return;
+        }
+
+        // walls under other windows aren't painted
+        let Some(pointer) = ctx
+            .pointer_hover_pos()
+            .filter(|pos| rect.contains(*pos) && ctx.layer_id_at(*pos) == Some(ui.layer_id()))
+        else {
+            return;
+        };
+
+        let collision_type = self.collision_brush.collision_type.clone();
+        let style = self.collision_styles.get(&collision_type);
+
+        let painter = ui.painter_at(rect);
+        painter.circle_stroke(
+            pointer,
+            BRUSH_RADIUS,
+            egui::Stroke::new(1.0_f32, style.color()),
+        );
+
+        if collision_type.is_empty() || !ctx.input(|i| i.pointer.primary_down()) {
+            return;
+        }
+
+        let to_screen = |point: Vec2| rect.min + self.camera.convert_to_camera(point);
+        let styles = &self.collision_styles;
+        let mapdata = &mut self.current_mapdata;
+        let mut painted = false;
+
+        let walls = mapdata
+            .walls
+            .iter_mut()
+            .map(|w| (w.start, w.end, &mut w.collision_type));
+        let labeled_walls = mapdata
+            .labeled_walls
+            .iter_mut()
+            .map(|w| (w.start, w.end, &mut w.collision_type));
+
+        for (start, end, wall_type) in walls.chain(labeled_walls) {
+            // hidden and locked walls are left alone
+            if *wall_type == collision_type || !styles.get(wall_type).is_editable() {
+                continue;
+            }
+
+            let (start, end) = (to_screen(start.get_vec2()), to_screen(end.get_vec2()));
+
+            if distance_to_segment(pointer, start, end) <= BRUSH_RADIUS {
+                *wall_type = collision_type.clone();
+                painted = true;
+            }
+        }
+
+        if painted {
+            if !mapdata.colbin_types.names.contains(&collision_type) {
+                mapdata.colbin_types.names.push(collision_type);
+            }
+
+            self.history.label_next("Paint Collision Type");
+        }
+    }
+
+    fn show_collision_brush_ui(&mut self, ctx: &egui::Context) {
+        let types = self.used_collision_types();
+        let styles = &self.collision_styles;
+        let brush = &mut self.collision_brush;
+        let mut done = false;
+
+        egui::Area::new(egui::Id::from("le_collision_brush"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 10.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .inner_margin(egui::Vec2::splat(8.0))
+                    .show(ui, |ui| {
+                        ui.label("Paint collision types");
+                        ui.label("Click or drag across walls to paint them.");
+
+                        ui.separator();
+                        ui.label("In this level");
+
+                        ui.horizontal_wrapped(|ui| {
+                            ui.set_max_width(250.0);
+
+                            for collision_type in types.iter() {
+                                let text = egui::RichText::new(collision_type)
+                                    .color(styles.get(collision_type).color());
+
+                                ui.selectable_value(
+                                    &mut brush.collision_type,
+                                    collision_type.clone(),
+                                    text,
+                                );
+                            }
+                        });
+
+                        ui.separator();
+
+                        egui::ComboBox::from_label("Collision Type")
+                            .selected_text(&brush.collision_type)
+                            .show_ui(ui, |ui| {
+                                for collision_type in COLLISION_TYPES {
+                                    ui.selectable_value(
+                                        &mut brush.collision_type,
+                                        collision_type.to_string(),
+                                        collision_type,
+                                    );
+                                }
+                            });
+
+                        ui.add(
+                            egui::TextEdit::singleline(&mut brush.collision_type).char_limit(0x20),
+                        );
+
+                        if ui.button("Done").on_hover_text("Escape").clicked() {
+                            done = true;
+                        }
+                    });
+            });
+
+        if done {
+            self.collision_brush.active = false;
+        }
+    }
+}
@@ -1,10 +1,14 @@
-use super::{EditMode, LevelEditor, ObjectType, le_object};
+use super::{EditMode, LevelEditor, ObjectType};
 // use super::{
 //     EditMode, LevelEditor, ObjectType
 // };
 
 use crate::quilt::{
-    game::{endata::*, mapdata::*},
+    game::{
+        bgst::{FIRST_NEAR_LAYER, LAYER_NAMES},
+        endata::*,
+        mapdata::*,
+    },
     util::comment::Comment,
 };
 
@@ -58,6 +62,8 @@ impl LevelEditor {
                 .on_hover_text("Any entities with a name of 'NONE' will be removed.")
                 .clicked()
             {
+                self.history.label_next("Remove 'NONE'");
+
                 self.current_mapdata
                     .gimmicks
                     .retain(|gimmick| gimmick.name != "NONE");
@@ -72,6 +78,20 @@ impl LevelEditor {
             if ui.button("Canvas Options").clicked() {
                 self.show_canvas_options = !self.show_canvas_options;
             }
+
+            if ui.button("Prefabs").clicked() {
+                self.prefabs.show_panel = !self.prefabs.show_panel;
+            }
+
+            if ui
+                .selectable_label(self.collision_brush.active, "Collision Brush")
+                .on_hover_text(
+                    "Paint a collision type onto walls by clicking or dragging across them.",
+                )
+                .clicked()
+            {
+                self.toggle_collision_brush();
+            }
         });
 
         // canvas options
@@ -116,6 +136,23 @@ impl LevelEditor {
                                 });
                         });
                     }
+
+                    ui.checkbox(&mut self.show_wall_normals, "Show wall normals")
+                        .on_hover_text("Draws a tick on the solid side of each wall, coloured by collision type.");
+
+                    ui.checkbox(&mut self.bounds.visible, "Show level bounds");
+
+                    ui.checkbox(&mut self.show_references, "Show all references")
+                        .on_hover_text("Draws an arrow from each object to the objects its parameters name. References of the selected objects are always drawn.");
+
+                    egui::CollapsingHeader::new("Collision Types")
+                        .id_salt("le_collision_types")
+                        .show(ui, |ui| {
+                            self.show_collision_style_ui(ui);
+                        });
+
+                    ui.separator();
+                    self.snap.show_ui(ui);
                 });
         }
         /*
@@ -135,36 +172,37 @@ impl LevelEditor {
             let painter = ui.painter_at(rect);
             painter.rect_filled(rect, 0.0, egui::Color32::BLACK);
 
+            // clicking empty space clears the selection, unless an object is being placed
+            if response.clicked()
+                && self.current_add_object.is_none()
+                && !self.wall_pen.active
+                && !self.collision_brush.active
+                && !ui.ctx().input(|i| i.modifiers.shift || i.modifiers.command)
+            {
+                self.deselect_all();
+            }
+
             // object placement
             self.process_object_addition(ui, &response, rect, painter);
 
-            /* rendering */
-
-            // bgst rendering
-            if self.render_bgst {
-                self.bgst_renderer.zoom = self.camera.zoom;
-
-                let bg_base_index = self
-                    .current_mapdata
-                    .gimmicks
-                    .iter()
-                    .position(|g| &g.name == "BG_BASE");
-
-                if let Some(bg_base_index) = bg_base_index {
-                    let bg_base = &self.current_mapdata.gimmicks[bg_base_index];
-
-                    // ensure the bg starts at the bottom left of the gimmick's rendering square
-                    let pos = egui::Vec2::new(
-                        bg_base.position.x - le_object::SQUARE_SIZE / 2.0,
-                        bg_base.position.y,
-                    );
+            if self.wall_pen.active {
+                self.process_wall_pen(ui, &response, rect);
+            }
 
-                    let pos = rect.min + self.camera.convert_to_camera(pos);
+            /* rendering */
 
-                    self.bgst_renderer.le_render(ui, rect, pos.to_vec2());
-                }
+            // far bgst layers are drawn behind everything else
+            if self.render_bgst
+                && let Some(anchor) = self.bgst_anchor()
+            {
+                self.bgst_renderer
+                    .le_render(ui, rect, &self.camera, anchor, 0..FIRST_NEAR_LAYER);
             }
 
+            self.draw_grid(ui, rect);
+            self.collision_styles.load_if_needed();
+            self.draw_label_highlight(ui, rect);
+
             if !matches!(self.wall_edit_mode, EditMode::Hide) {
                 self.update_walls(ui, rect);
             }
@@ -173,6 +211,8 @@ impl LevelEditor {
                 self.update_labeled_walls(ui, rect);
             }
 
+            self.draw_wall_normals(ui, rect);
+
             if !matches!(self.common_gimmick_edit_mode, EditMode::Hide) {
                 let _ = self.update_common_gimmicks(ui, rect);
             }
@@ -183,6 +223,7 @@ impl LevelEditor {
 
             if !matches!(self.path_edit_mode, EditMode::Hide) {
                 self.update_paths(ui, rect);
+                self.process_path_overlay(ui, rect);
             }
 
             if !matches!(self.zone_edit_mode, EditMode::Hide) {
@@ -197,7 +238,32 @@ impl LevelEditor {
                 self.update_comments(ui, rect);
             }
 
+            self.draw_enemy_path_links(ui, rect);
             self.update_enemies(ui, rect);
+            self.process_enemy_positions(ui, rect);
+            self.process_level_bounds(ui, rect);
+            self.draw_references(ui, rect);
+            self.draw_topology_issues(ui, rect);
+            self.draw_lint_findings(ui, rect);
+
+            if self.collision_brush.active {
+                self.process_collision_brush(ui, rect);
+            }
+
+            // near bgst layers cover gameplay objects, as they do in-game
+            if self.render_bgst
+                && let Some(anchor) = self.bgst_anchor()
+            {
+                self.bgst_renderer.le_render(
+                    ui,
+                    rect,
+                    &self.camera,
+                    anchor,
+                    FIRST_NEAR_LAYER..LAYER_NAMES.len() as i16,
+                );
+            }
+
+            self.draw_collision_legend(ui, rect);
 
             /* end rendering */
 
@@ -232,6 +298,12 @@ impl LevelEditor {
             return;
         }
 
+        if let Some(ObjectType::Prefab(name)) = &self.current_add_object {
+            let name = name.clone();
+            self.process_prefab_placement(ui, response, rect, &name);
+            return;
+        }
+
         if let Some(object_type) = &self.current_add_object {
             if let Some(pointer_pos) = response.hover_pos() {
                 painter.circle_filled(pointer_pos, 1.0, egui::Color32::GRAY);
@@ -262,11 +334,19 @@ impl LevelEditor {
             {
                 pointer_pos -= rect.min.to_vec2();
 
+                let pointer_world = self.camera.convert_from_camera(pointer_pos.to_vec2());
+                let world_pos = self.snap.snap_point(pointer_world);
+
+                // new walls start at the end of an existing wall if one is close enough
+                let wall_start = self
+                    .snap
+                    .snap_to_vertex(pointer_world, &self.wall_vertices(), None, self.camera.zoom)
+                    .unwrap_or(world_pos);
+
                 match object_type {
                     ObjectType::Wall => {
                         let collision_type = String::from("NML");
-                        let start = self.camera.convert_from_camera(pointer_pos.to_vec2());
-                        let start = Point2D::from_vec2(start);
+                        let start = Point2D::from_vec2(wall_start);
                         let end = Point2D {
                             x: start.x + 5.0,
                             y: start.y,
@@ -284,8 +364,7 @@ impl LevelEditor {
 
                     ObjectType::LabeledWall => {
                         let collision_type = String::from("NML");
-                        let start = self.camera.convert_from_camera(pointer_pos.to_vec2());
-                        let start = Point2D::from_vec2(start);
+                        let start = Point2D::from_vec2(wall_start);
                         let end = Point2D {
                             x: start.x + 5.0,
                             y: start.y,
@@ -304,10 +383,7 @@ impl LevelEditor {
                     ObjectType::CommonGimmick(hex) => {
                         let mut gmk = CommonGimmick::default();
 
-                        let pos = self
-                            .camera
-                            .convert_from_camera(pointer_pos.to_vec2())
-                            .to_pos2();
+                        let pos = world_pos.to_pos2();
                         gmk.position = Point2D::from_pos2(pos).get_point3d();
                         gmk.hex = hex.to_owned();
                         self.current_mapdata.common_gimmicks.push(gmk);
@@ -332,10 +408,7 @@ impl LevelEditor {
                     ObjectType::Gimmick => {
                         let mut gmk = Gimmick::default();
 
-                        let pos = self
-                            .camera
-                            .convert_from_camera(pointer_pos.to_vec2())
-                            .to_pos2();
+                        let pos = world_pos.to_pos2();
 
                         gmk.position = Point2D::from_pos2(pos).get_point3d();
                         gmk.name = String::from("NEW");
@@ -345,7 +418,7 @@ impl LevelEditor {
                     ObjectType::Path => {
                         let mut path = Path::default();
 
-                        let first = self.camera.convert_from_camera(pointer_pos.to_vec2());
+                        let first = world_pos;
                         path.points.push(Point2D {
                             x: first.x,
                             y: first.y,
@@ -362,10 +435,7 @@ impl LevelEditor {
 
                     ObjectType::Zone => {
                         let mut zone = Zone::default();
-                        let start = self
-                            .camera
-                            .convert_from_camera(pointer_pos.to_vec2())
-                            .to_pos2();
+                        let start = world_pos.to_pos2();
                         let end = start + egui::Vec2::splat(5.0);
 
                         zone.bounds_start = Point2D::from_pos2(start);
@@ -376,7 +446,7 @@ impl LevelEditor {
 
                     ObjectType::CourseInfo => {
                         let mut course_info = CourseInfo::default();
-                        let pos = self.camera.convert_from_camera(pointer_pos.to_vec2());
+                        let pos = world_pos;
                         course_info.position = Point2D::from_vec2(pos).get_point3d();
                         course_info.name = String::from("NEW");
                         self.current_mapdata.course_infos.push(course_info);
@@ -384,11 +454,11 @@ impl LevelEditor {
 
                     ObjectType::Enemy => {
                         let mut enemy = Enemy::new();
-                        let pos = self
-                            .camera
-                            .convert_from_camera(pointer_pos.to_vec2())
-                            .to_pos2();
+                        let pos = world_pos.to_pos2();
                         enemy.position_1 = Point2D::from_pos2(pos).get_point3d();
+                        // the secondary positions start on the enemy rather than at the origin
+                        enemy.position_2 = enemy.position_1.clone();
+                        enemy.position_3 = enemy.position_1.clone();
                         self.current_endata.enemies.push(enemy);
                     }
 
@@ -396,10 +466,7 @@ impl LevelEditor {
                         // the file this comment is for
                         let file = self.archive_contents[self.selected_file_index].0.to_owned();
 
-                        let pos = self
-                            .camera
-                            .convert_from_camera(pointer_pos.to_vec2())
-                            .to_pos2();
+                        let pos = world_pos.to_pos2();
                         let position = Point2D::from_pos2(pos);
 
                         if self.comments.is_none() {
@@ -412,6 +479,9 @@ impl LevelEditor {
                             ..Default::default()
                         });
                     }
+
+                    // placed by process_prefab_placement
+                    ObjectType::Prefab(_) => {}
                 }
                 self.current_add_object = None;
             }
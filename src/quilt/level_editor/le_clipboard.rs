@@ -0,0 +1,315 @@
+use super::{LevelEditor, ObjectRef, le_selection::Translate};
+
+use crate::quilt::{
+    common::{ObjectId, Point2D},
+    game::{endata::Enemy, mapdata::*},
+    util::comment::Comment,
+};
+
+use egui::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Marks clipboard text as objects copied from the level editor.
+const CLIPBOARD_FORMAT: &str = "quilt-objects";
+
+/// How far duplicated objects are moved from the originals, in world units.
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(2.0, -2.0);
+
+/// A group of objects, as copied to the clipboard or saved as a prefab.
+/// Names are stored with the objects rather than as indices into the level's name tables,
+/// so that they can be pasted into any level.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub(super) struct ObjectGroup {
+    format: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub walls: Vec<Wall>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labeled_walls: Vec<LabeledWall>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub common_gimmicks: Vec<CommonGimmick>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gimmicks: Vec<Gimmick>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<Path>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<Zone>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub course_infos: Vec<CourseInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enemies: Vec<Enemy>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+}
+
+impl ObjectGroup {
+    /// Reads objects from text, if it was written by the level editor.
+    pub fn from_json(text: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(text)
+            .ok()
+            .filter(|group| group.format == CLIPBOARD_FORMAT)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.walls.is_empty()
+            && self.labeled_walls.is_empty()
+            && self.common_gimmicks.is_empty()
+            && self.gimmicks.is_empty()
+            && self.paths.is_empty()
+            && self.zones.is_empty()
+            && self.course_infos.is_empty()
+            && self.enemies.is_empty()
+            && self.comments.is_empty()
+    }
+
+    /// Every point that makes up the objects.
+    pub fn points(&self) -> Vec<Point2D> {
+        let mut points = Vec::new();
+
+        for wall in self.walls.iter() {
+            points.extend([wall.start, wall.end]);
+        }
+
+        for wall in self.labeled_walls.iter() {
+            points.extend([wall.start, wall.end]);
+        }
+
+        points.extend(
+            self.common_gimmicks
+                .iter()
+                .map(|o| o.position.get_point2d()),
+        );
+        points.extend(self.gimmicks.iter().map(|o| o.position.get_point2d()));
+
+        for path in self.paths.iter() {
+            points.extend(path.points.iter().copied());
+        }
+
+        for zone in self.zones.iter() {
+            points.extend([zone.bounds_start, zone.bounds_end]);
+        }
+
+        points.extend(self.course_infos.iter().map(|o| o.position.get_point2d()));
+        for enemy in self.enemies.iter() {
+            points.extend(
+                [&enemy.position_1, &enemy.position_2, &enemy.position_3].map(|p| p.get_point2d()),
+            );
+        }
+
+        points.extend(self.comments.iter().map(|o| o.position));
+
+        points
+    }
+
+    /// The center of the objects' bounding box.
+    pub fn center(&self) -> Option<Vec2> {
+        let points = self.points();
+        let first = points.first()?.get_vec2();
+
+        let (min, max) = points.iter().fold((first, first), |(min, max), point| {
+            (min.min(point.get_vec2()), max.max(point.get_vec2()))
+        });
+
+        Some((min + max) / 2.0)
+    }
+
+    pub fn translate(&mut self, delta: Vec2) {
+        fn translate_all<T: Translate>(objects: &mut [T], delta: Vec2) {
+            for object in objects.iter_mut() {
+                object.translate(delta);
+            }
+        }
+
+        translate_all(&mut self.walls, delta);
+        translate_all(&mut self.labeled_walls, delta);
+        translate_all(&mut self.common_gimmicks, delta);
+        translate_all(&mut self.gimmicks, delta);
+        translate_all(&mut self.paths, delta);
+        translate_all(&mut self.zones, delta);
+        translate_all(&mut self.course_infos, delta);
+        translate_all(&mut self.enemies, delta);
+        translate_all(&mut self.comments, delta);
+    }
+}
+
+impl LevelEditor {
+    pub(super) fn copy_selection(&self) -> ObjectGroup {
+        fn selected<T: Clone>(objects: &[T], is_selected: fn(&T) -> bool) -> Vec<T> {
+            objects.iter().filter(|o| is_selected(o)).cloned().collect()
+        }
+
+        let mapdata = &self.current_mapdata;
+
+        ObjectGroup {
+            format: CLIPBOARD_FORMAT.to_string(),
+            walls: selected(&mapdata.walls, |o| o.is_selected),
+            labeled_walls: selected(&mapdata.labeled_walls, |o| o.is_selected),
+            common_gimmicks: selected(&mapdata.common_gimmicks, |o| o.is_selected),
+            gimmicks: selected(&mapdata.gimmicks, |o| o.is_selected),
+            paths: selected(&mapdata.paths, |o| o.is_selected),
+            zones: selected(&mapdata.zones, |o| o.is_selected),
+            course_infos: selected(&mapdata.course_infos, |o| o.is_selected),
+            enemies: selected(&self.current_endata.enemies, |o| o.is_selected),
+            comments: selected(self.comments.as_deref().unwrap_or_default(), |o| {
+                o.is_selected
+            }),
+        }
+    }
+
+    /// Adds the objects to the current level and selects them.
+    /// Any names they use that aren't in the level's name tables are added to them.
+    pub(super) fn paste_objects(&mut self, ctx: &egui::Context, group: ObjectGroup) {
+        self.deselect_all();
+
+        let mut pasted = Vec::new();
+        let mapdata = &mut self.current_mapdata;
+
+        for mut wall in group.walls {
+            wall.id = ObjectId::new();
+            pasted.push(ObjectRef::Wall(wall.id));
+
+            if !mapdata.colbin_types.names.contains(&wall.collision_type) {
+                mapdata.colbin_types.names.push(wall.collision_type.clone());
+            }
+
+            mapdata.walls.push(wall);
+        }
+
+        for mut wall in group.labeled_walls {
+            wall.id = ObjectId::new();
+            pasted.push(ObjectRef::LabeledWall(wall.id));
+
+            if !mapdata.colbin_types.names.contains(&wall.collision_type) {
+                mapdata.colbin_types.names.push(wall.collision_type.clone());
+            }
+
+            if !mapdata.wall_labels.names.contains(&wall.label) {
+                mapdata.wall_labels.names.push(wall.label.clone());
+            }
+
+            mapdata.labeled_walls.push(wall);
+        }
+
+        let mut hex_names = Vec::new();
+
+        for mut gmk in group.common_gimmicks {
+            gmk.id = ObjectId::new();
+            pasted.push(ObjectRef::CommonGimmick(gmk.id));
+
+            if !mapdata.common_gimmick_names.hex_names.contains(&gmk.hex) {
+                mapdata.common_gimmick_names.hex_names.push(gmk.hex.clone());
+            }
+
+            hex_names.push(gmk.hex.clone());
+            mapdata.common_gimmicks.push(gmk);
+        }
+
+        for mut gmk in group.gimmicks {
+            gmk.id = ObjectId::new();
+            pasted.push(ObjectRef::Gimmick(gmk.id));
+            mapdata.gimmicks.push(gmk);
+        }
+
+        for mut path in group.paths {
+            path.id = ObjectId::new();
+            pasted.push(ObjectRef::Path(path.id));
+            mapdata.paths.push(path);
+        }
+
+        for mut zone in group.zones {
+            zone.id = ObjectId::new();
+            pasted.push(ObjectRef::Zone(zone.id));
+            mapdata.zones.push(zone);
+        }
+
+        for mut info in group.course_infos {
+            info.id = ObjectId::new();
+            pasted.push(ObjectRef::CourseInfo(info.id));
+            mapdata.course_infos.push(info);
+        }
+
+        for mut enemy in group.enemies {
+            enemy.id = ObjectId::new();
+            pasted.push(ObjectRef::Enemy(enemy.id));
+            self.current_endata.enemies.push(enemy);
+        }
+
+        if !group.comments.is_empty() {
+            // comments belong to whichever file they're pasted into
+            let file = self.archive_contents[self.selected_file_index].0.to_owned();
+            let comments = self.comments.get_or_insert_with(Vec::new);
+
+            for mut comment in group.comments {
+                comment.id = ObjectId::new();
+                comment.file = file.clone();
+                pasted.push(ObjectRef::Comment(comment.id));
+                comments.push(comment);
+            }
+        }
+
+        for hex in hex_names {
+            self.add_common_gimmick_texture(ctx, &hex);
+        }
+
+        for object in pasted {
+            self.select_object(object);
+        }
+    }
+
+    /// Handles Ctrl+C, Ctrl+V and Ctrl+D.
+    /// Pasted objects are centered on the mouse if it's over the canvas.
+    pub(super) fn handle_clipboard_shortcuts(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let ctx = ui.ctx();
+
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let mut copied = false;
+        let mut pasted_text = None;
+
+        ctx.input(|i| {
+            for event in i.events.iter() {
+                match event {
+                    egui::Event::Copy => copied = true,
+                    egui::Event::Paste(text) => pasted_text = Some(text.clone()),
+                    _ => {}
+                }
+            }
+        });
+
+        let duplicate = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D);
+        let duplicated = ctx.input_mut(|i| i.consume_shortcut(&duplicate));
+
+        if copied
+            && !self.selected_objects.is_empty()
+            && let Ok(text) = serde_json::to_string_pretty(&self.copy_selection())
+        {
+            ctx.copy_text(text);
+        }
+
+        if duplicated && !self.selected_objects.is_empty() {
+            let mut group = self.copy_selection();
+            group.translate(DUPLICATE_OFFSET);
+
+            self.history.label_next("Duplicate");
+            self.paste_objects(ctx, group);
+        }
+
+        // text that isn't from the level editor is ignored
+        if let Some(text) = pasted_text
+            && let Some(mut group) = ObjectGroup::from_json(&text)
+            && !group.is_empty()
+        {
+            if let Some(pos) = ctx.pointer_hover_pos()
+                && response.rect.contains(pos)
+                && let Some(center) = group.center()
+            {
+                let target = self.camera.convert_from_camera(pos - response.rect.min);
+                group.translate(target - center);
+            }
+
+            self.history.label_next("Paste");
+            self.paste_objects(ctx, group);
+        }
+    }
+}
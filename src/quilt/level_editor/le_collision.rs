@@ -0,0 +1,357 @@
+use super::{COLLISION_TYPES, LevelEditor};
+
+use crate::quilt::common::quilt_res_path;
+
+use anyhow::Result;
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::PathBuf};
+
+const STYLE_FILE_NAME: &str = "collision_styles.json";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineStyle {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Solid => "Solid",
+            Self::Dashed => "Dashed",
+            Self::Dotted => "Dotted",
+        }
+    }
+}
+
+/// How walls of one collision type are drawn, and whether they can be edited.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CollisionStyle {
+    color: [u8; 3],
+    pub line_style: LineStyle,
+    pub visible: bool,
+    /// Locked walls are drawn, but can't be selected or moved.
+    pub locked: bool,
+}
+
+impl CollisionStyle {
+    /// The style a collision type has until it's changed.
+    /// `THROUGH` walls are dashed and camera walls are dotted,
+    /// and the colour comes from the name, so it's the same in every level.
+    fn default_for(collision_type: &str) -> Self {
+        let color = if collision_type == "NML" {
+            [0xFF, 0xFF, 0xFF]
+        } else {
+            // FNV-1a
+            let hash = collision_type.bytes().fold(0x811C9DC5_u32, |hash, b| {
+                (hash ^ b as u32).wrapping_mul(0x01000193)
+            });
+
+            let hue = (hash % 360) as f32 / 360.0;
+            let color = egui::Color32::from(egui::ecolor::Hsva::new(hue, 0.65, 1.0, 1.0));
+            [color.r(), color.g(), color.b()]
+        };
+
+        let line_style = if collision_type.starts_with("CAMERA") {
+            LineStyle::Dotted
+        } else if collision_type.contains("THROUGH") {
+            LineStyle::Dashed
+        } else {
+            LineStyle::Solid
+        };
+
+        Self {
+            color,
+            line_style,
+            visible: true,
+            locked: false,
+        }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        let [r, g, b] = self.color;
+        egui::Color32::from_rgb(r, g, b)
+    }
+
+    /// Whether walls with this style can be selected and moved.
+    pub fn is_editable(&self) -> bool {
+        self.visible && !self.locked
+    }
+
+    /// Draws a line in this style, in `color` rather than the style's colour.
+    pub fn draw_line(&self, painter: &egui::Painter, points: [Pos2; 2], color: egui::Color32) {
+        let stroke = egui::Stroke::new(1.0_f32, color);
+
+        match self.line_style {
+            LineStyle::Solid => _ = painter.line_segment(points, stroke),
+            LineStyle::Dashed => {
+                painter.extend(egui::Shape::dashed_line(&points, stroke, 6.0, 4.0));
+            }
+            LineStyle::Dotted => {
+                painter.extend(egui::Shape::dotted_line(&points, color, 4.0, 1.0));
+            }
+        }
+    }
+}
+
+/// The style of each collision type that's been changed from its default.
+#[derive(Default)]
+pub struct CollisionStyles {
+    styles: HashMap<String, CollisionStyle>,
+    loaded: bool,
+    pub show_legend: bool,
+    /// Only collision types containing this are listed in the canvas options.
+    filter: String,
+    /// Whether every known collision type is listed, rather than just the ones in the level.
+    list_all: bool,
+}
+
+impl CollisionStyles {
+    fn file_path() -> Result<PathBuf> {
+        Ok(quilt_res_path()?.join(STYLE_FILE_NAME))
+    }
+
+    /// Reads the saved styles, the first time they're needed.
+    pub fn load_if_needed(&mut self) {
+        if !self.loaded
+            && let Err(e) = self.load()
+        {
+            eprintln!("Failed to load collision styles. Reason: {:?}", e);
+        }
+    }
+
+    fn load(&mut self) -> Result<()> {
+        self.loaded = true;
+        let path = Self::file_path()?;
+
+        if fs::exists(&path)? {
+            self.styles = serde_json::from_str(&fs::read_to_string(path)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(
+            Self::file_path()?,
+            serde_json::to_string_pretty(&self.styles)?,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get(&self, collision_type: &str) -> CollisionStyle {
+        self.styles
+            .get(collision_type)
+            .copied()
+            .unwrap_or_else(|| CollisionStyle::default_for(collision_type))
+    }
+
+    fn set(&mut self, collision_type: &str, style: CollisionStyle) {
+        if style == CollisionStyle::default_for(collision_type) {
+            self.styles.remove(collision_type);
+        } else {
+            self.styles.insert(collision_type.to_string(), style);
+        }
+    }
+}
+
+impl LevelEditor {
+    /// The collision types used by walls in the current level, in the order they're listed in the level.
+    pub(super) fn used_collision_types(&self) -> Vec<String> {
+        let mapdata = &self.current_mapdata;
+        let mut types: Vec<String> = Vec::new();
+
+        let wall_types = mapdata
+            .walls
+            .iter()
+            .map(|w| &w.collision_type)
+            .chain(mapdata.labeled_walls.iter().map(|w| &w.collision_type));
+
+        for collision_type in mapdata.colbin_types.names.iter().chain(wall_types) {
+            if !types.contains(collision_type) {
+                types.push(collision_type.clone());
+            }
+        }
+
+        types
+    }
+
+    /// Lists each collision type with its style, and whether it's shown or locked.
+    pub(super) fn show_collision_style_ui(&mut self, ui: &mut egui::Ui) {
+        let mut types = self.used_collision_types();
+
+        if self.collision_styles.list_all {
+            for collision_type in COLLISION_TYPES {
+                if !types.iter().any(|t| t == collision_type) {
+                    types.push(collision_type.to_string());
+                }
+            }
+        }
+
+        let styles = &mut self.collision_styles;
+
+        ui.checkbox(&mut styles.show_legend, "Show legend");
+        ui.checkbox(&mut styles.list_all, "List all collision types")
+            .on_hover_text("Otherwise, only the types used in this level are listed.");
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut styles.filter)
+                    .hint_text("Filter")
+                    .desired_width(120.0),
+            );
+        });
+
+        let filter = styles.filter.to_uppercase();
+        types.retain(|t| t.to_uppercase().contains(&filter));
+
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            for (text, visible) in [("Show matching", true), ("Hide matching", false)] {
+                if ui.small_button(text).clicked() {
+                    for collision_type in types.iter() {
+                        let mut style = styles.get(collision_type);
+                        style.visible = visible;
+                        styles.set(collision_type, style);
+                    }
+
+                    changed = true;
+                }
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .id_salt("le_collision_styles")
+            .max_height(250.0)
+            .show(ui, |ui| {
+                egui::Grid::new("le_collision_style_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Type");
+                        ui.label("Colour");
+                        ui.label("Line");
+                        ui.label("Show");
+                        ui.label("Lock");
+                        ui.end_row();
+
+                        for collision_type in types.iter() {
+                            let mut style = styles.get(collision_type);
+                            let mut color = style.color();
+
+                            ui.label(collision_type);
+
+                            if ui.color_edit_button_srgba(&mut color).changed() {
+                                let [r, g, b, _] = color.to_array();
+                                style.color = [r, g, b];
+                            }
+
+                            egui::ComboBox::from_id_salt(("le_collision_line", collision_type))
+                                .selected_text(style.line_style.name())
+                                .width(70.0)
+                                .show_ui(ui, |ui| {
+                                    for line_style in
+                                        [LineStyle::Solid, LineStyle::Dashed, LineStyle::Dotted]
+                                    {
+                                        ui.selectable_value(
+                                            &mut style.line_style,
+                                            line_style,
+                                            line_style.name(),
+                                        );
+                                    }
+                                });
+
+                            ui.checkbox(&mut style.visible, "");
+                            ui.checkbox(&mut style.locked, "");
+                            ui.end_row();
+
+                            if style != styles.get(collision_type) {
+                                styles.set(collision_type, style);
+                                changed = true;
+                            }
+                        }
+                    });
+            });
+
+        if ui.button("Reset to defaults").clicked() {
+            styles.styles.clear();
+            changed = true;
+        }
+
+        if changed && let Err(e) = styles.save() {
+            eprintln!("Failed to save collision styles. Reason: {:?}", e);
+        }
+    }
+
+    /// Draws the style of each collision type used in the level, in the bottom left of the canvas.
+    pub(super) fn draw_collision_legend(&self, ui: &egui::Ui, rect: Rect) {
+        if !self.collision_styles.show_legend {
+            return;
+        }
+
+        let types = self.used_collision_types();
+
+        if types.is_empty() {
+            return;
+        }
+
+        let painter = ui.painter_at(rect);
+        let font = egui::FontId::proportional(12.0);
+        let row_height = 16.0;
+        let line_width = 24.0;
+
+        let text_width = types
+            .iter()
+            .map(|t| {
+                painter
+                    .layout_no_wrap(t.clone(), font.clone(), egui::Color32::WHITE)
+                    .size()
+                    .x
+            })
+            .fold(0.0, f32::max);
+
+        let size = Vec2::new(
+            line_width + text_width + 24.0,
+            row_height * types.len() as f32 + 8.0,
+        );
+        let legend_rect = Rect::from_min_size(
+            Pos2::new(rect.min.x + 10.0, rect.max.y - 10.0 - size.y),
+            size,
+        );
+
+        painter.rect_filled(
+            legend_rect,
+            4.0,
+            egui::Color32::from_rgba_unmultiplied(0x00, 0x00, 0x00, 0xC0),
+        );
+
+        for (i, collision_type) in types.iter().enumerate() {
+            let style = self.collision_styles.get(collision_type);
+            let y = legend_rect.min.y + 4.0 + row_height * (i as f32 + 0.5);
+            let x = legend_rect.min.x + 8.0;
+
+            let color = if style.visible {
+                style.color()
+            } else {
+                egui::Color32::DARK_GRAY
+            };
+
+            style.draw_line(
+                &painter,
+                [Pos2::new(x, y), Pos2::new(x + line_width, y)],
+                color,
+            );
+
+            painter.text(
+                Pos2::new(x + line_width + 8.0, y),
+                egui::Align2::LEFT_CENTER,
+                collision_type,
+                font.clone(),
+                color,
+            );
+        }
+    }
+}
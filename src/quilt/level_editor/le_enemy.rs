@@ -0,0 +1,268 @@
+use super::{LevelEditor, le_object::ENEMY_COLOR, le_snap::drag_position};
+
+use crate::quilt::{common::Point3D, game::endata::Enemy};
+
+use egui::{Pos2, Rect, Vec2};
+
+/// The radius of the secondary position handles, in pixels.
+const HANDLE_RADIUS: f32 = 5.0;
+
+/// The length of the ticks at the ends of a patrol range, in pixels.
+const RANGE_TICK: f32 = 6.0;
+
+/// How an enemy's secondary positions are used by its behavior.
+enum PatrolPreview {
+    /// Walks left and right between the x of each position, at the height of the enemy.
+    Horizontal,
+    /// Moves up and down between the y of each position, in line with the enemy.
+    Vertical,
+    /// Slides along the line between the positions.
+    Line,
+    None,
+}
+
+impl PatrolPreview {
+    fn for_behavior(behavior: &str) -> Self {
+        match behavior {
+            "WALK_AREA" => Self::Horizontal,
+            "UP_DOWN" => Self::Vertical,
+            "SLIDE" => Self::Line,
+            _ => Self::None,
+        }
+    }
+}
+
+fn point(position: &Point3D) -> Vec2 {
+    position.get_point2d().get_vec2()
+}
+
+/// Draws a line with a tick across each end.
+fn draw_range(painter: &egui::Painter, start: Pos2, end: Pos2) {
+    let stroke = egui::Stroke::new(3.0_f32, ENEMY_COLOR.gamma_multiply(0.5));
+    painter.line_segment([start, end], stroke);
+
+    let across = (end - start).normalized().rot90() * RANGE_TICK;
+
+    for pos in [start, end] {
+        painter.line_segment([pos - across, pos + across], stroke);
+    }
+}
+
+/// Draws the range the enemy patrols, as its behavior uses its secondary positions.
+fn draw_patrol_preview(painter: &egui::Painter, enemy: &Enemy, to_screen: impl Fn(Vec2) -> Pos2) {
+    let (main, second, third) = (
+        point(&enemy.position_1),
+        point(&enemy.position_2),
+        point(&enemy.position_3),
+    );
+
+    let (start, end) = match PatrolPreview::for_behavior(&enemy.behavior) {
+        PatrolPreview::Horizontal => (Vec2::new(second.x, main.y), Vec2::new(third.x, main.y)),
+        PatrolPreview::Vertical => (Vec2::new(main.x, second.y), Vec2::new(main.x, third.y)),
+        PatrolPreview::Line => (second, third),
+        PatrolPreview::None => return,
+    };
+
+    if start != end {
+        draw_range(painter, to_screen(start), to_screen(end));
+    }
+}
+
+impl LevelEditor {
+    /// Draws the secondary positions of the selected enemies as handles linked to their main
+    /// position, which move the positions when dragged, along with the range they patrol.
+    pub(super) fn process_enemy_positions(&mut self, ui: &egui::Ui, canvas_rect: Rect) {
+        let painter = ui.painter_at(canvas_rect);
+        let camera = &self.camera;
+        let to_screen = |point: Vec2| canvas_rect.min + camera.convert_to_camera(point);
+        let font = egui::FontId::proportional(11.0);
+
+        for enemy in self.current_endata.enemies.iter_mut() {
+            if !enemy.is_selected {
+                continue;
+            }
+
+            draw_patrol_preview(&painter, enemy, to_screen);
+
+            let main = to_screen(point(&enemy.position_1));
+
+            for (number, position) in [(2, &mut enemy.position_2), (3, &mut enemy.position_3)] {
+                let pos = to_screen(point(position));
+
+                painter.extend(egui::Shape::dashed_line(
+                    &[main, pos],
+                    egui::Stroke::new(1.0_f32, ENEMY_COLOR),
+                    4.0,
+                    3.0,
+                ));
+
+                painter.circle_stroke(pos, HANDLE_RADIUS, egui::Stroke::new(1.5_f32, ENEMY_COLOR));
+
+                painter.text(
+                    pos + Vec2::new(HANDLE_RADIUS + 2.0, -HANDLE_RADIUS - 2.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    number.to_string(),
+                    font.clone(),
+                    ENEMY_COLOR,
+                );
+
+                let resp = ui.interact(
+                    canvas_rect.intersect(Rect::from_center_size(
+                        pos,
+                        Vec2::splat(HANDLE_RADIUS * 2.0),
+                    )),
+                    egui::Id::new((enemy.id, "position", number)),
+                    egui::Sense::drag(),
+                );
+
+                if let Some(target) =
+                    drag_position(ui.ctx(), &resp, camera, canvas_rect, point(position))
+                {
+                    let target = self.snap.snap_point(target);
+                    position.x = target.x;
+                    position.y = target.y;
+                }
+            }
+        }
+    }
+}
+
+/// A path that was renamed while enemies still use its old name.
+pub struct PathRename {
+    old: String,
+    new: String,
+}
+
+/// The closest point on a path to `pos`, in world units.
+fn closest_point_on_path(points: &[Vec2], pos: Vec2) -> Option<Vec2> {
+    if let [point] = points {
+        return Some(*point);
+    }
+
+    points
+        .windows(2)
+        .map(|segment| {
+            let (start, end) = (segment[0], segment[1]);
+            let direction = end - start;
+
+            let t = if direction.length_sq() == 0.0 {
+                0.0
+            } else {
+                ((pos - start).dot(direction) / direction.length_sq()).clamp(0.0, 1.0)
+            };
+
+            start + direction * t
+        })
+        .min_by(|a, b| (*a - pos).length().total_cmp(&(*b - pos).length()))
+}
+
+impl LevelEditor {
+    /// Draws a line from each enemy to the closest point on the path it uses.
+    /// Enemies using a path that doesn't exist are marked instead.
+    pub(super) fn draw_enemy_path_links(&self, ui: &egui::Ui, canvas_rect: Rect) {
+        let painter = ui.painter_at(canvas_rect);
+        let to_screen = |point: Vec2| canvas_rect.min + self.camera.convert_to_camera(point);
+
+        for enemy in self.current_endata.enemies.iter() {
+            if enemy.path_name.is_empty() {
+                continue;
+            }
+
+            let pos = point(&enemy.position_1);
+
+            let Some(path) = self
+                .current_mapdata
+                .paths
+                .iter()
+                .find(|p| p.name == enemy.path_name)
+            else {
+                painter.text(
+                    to_screen(pos) + Vec2::new(8.0, 8.0),
+                    egui::Align2::LEFT_TOP,
+                    "?",
+                    egui::FontId::proportional(14.0),
+                    egui::Color32::YELLOW,
+                );
+
+                continue;
+            };
+
+            let points: Vec<Vec2> = path.points.iter().map(|p| p.get_vec2()).collect();
+
+            if let Some(target) = closest_point_on_path(&points, pos) {
+                let alpha = if enemy.is_selected { 1.0 } else { 0.4 };
+
+                painter.extend(egui::Shape::dashed_line(
+                    &[to_screen(pos), to_screen(target)],
+                    egui::Stroke::new(1.0_f32, ENEMY_COLOR.gamma_multiply(alpha)),
+                    3.0,
+                    3.0,
+                ));
+            }
+        }
+    }
+
+    /// Offers to update the enemies using a path's old name after it's renamed.
+    pub(super) fn offer_path_rename(&mut self, old: String, new: String) {
+        if old != new
+            && !new.is_empty()
+            && self
+                .current_endata
+                .enemies
+                .iter()
+                .any(|e| e.path_name == old)
+        {
+            self.path_rename = Some(PathRename { old, new });
+        }
+    }
+
+    pub(super) fn show_path_rename_ui(&mut self, ctx: &egui::Context) {
+        let Some(rename) = &self.path_rename else {
+            return;
+        };
+
+        let count = self
+            .current_endata
+            .enemies
+            .iter()
+            .filter(|e| e.path_name == rename.old)
+            .count();
+
+        let mut update = false;
+        let mut dismiss = count == 0;
+
+        egui::Window::new("Update Enemies")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{count} enemies use the path '{}'. Update them to use '{}'?",
+                    rename.old, rename.new
+                ));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Update").clicked() {
+                        update = true;
+                    }
+
+                    if ui.button("Keep").clicked() {
+                        dismiss = true;
+                    }
+                });
+            });
+
+        if update {
+            self.history.label_next("Update Enemy Paths");
+
+            for enemy in self.current_endata.enemies.iter_mut() {
+                if enemy.path_name == rename.old {
+                    enemy.path_name = rename.new.clone();
+                }
+            }
+        }
+
+        if update || dismiss {
+            self.path_rename = None;
+        }
+    }
+}
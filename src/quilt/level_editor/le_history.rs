@@ -0,0 +1,555 @@
+use super::LevelEditor;
+use crate::quilt::{
+    common::Point2D,
+    game::{endata::Enemy, mapdata::*},
+    history::{Undo, UndoStack},
+    util::comment::Comment,
+};
+
+/// Objects from one of the level's lists.
+#[derive(Clone)]
+enum Objects {
+    Walls(Vec<Wall>),
+    LabeledWalls(Vec<LabeledWall>),
+    CommonGimmicks(Vec<CommonGimmick>),
+    Gimmicks(Vec<Gimmick>),
+    Paths(Vec<Path>),
+    Zones(Vec<Zone>),
+    CourseInfos(Vec<CourseInfo>),
+    Enemies(Vec<Enemy>),
+    Comments(Vec<Comment>),
+    CommonGimmickNames(Vec<String>),
+    CollisionTypes(Vec<String>),
+    WallLabels(Vec<String>),
+    /// The level's bounds, as a single minimum and maximum.
+    Bounds(Vec<[Point2D; 2]>),
+}
+
+impl Objects {
+    fn len(&self) -> usize {
+        match self {
+            Self::Walls(v) => v.len(),
+            Self::LabeledWalls(v) => v.len(),
+            Self::CommonGimmicks(v) => v.len(),
+            Self::Gimmicks(v) => v.len(),
+            Self::Paths(v) => v.len(),
+            Self::Zones(v) => v.len(),
+            Self::CourseInfos(v) => v.len(),
+            Self::Enemies(v) => v.len(),
+            Self::Comments(v) => v.len(),
+            Self::CommonGimmickNames(v) | Self::CollisionTypes(v) | Self::WallLabels(v) => v.len(),
+            Self::Bounds(v) => v.len(),
+        }
+    }
+
+    /// Name tables change along with the objects that use them,
+    /// so they aren't mentioned in history labels.
+    fn is_name_table(&self) -> bool {
+        matches!(
+            self,
+            Self::CommonGimmickNames(_) | Self::CollisionTypes(_) | Self::WallLabels(_)
+        )
+    }
+
+    fn type_name(&self, count: usize) -> &'static str {
+        let (singular, plural) = match self {
+            Self::Walls(_) => ("Wall", "Walls"),
+            Self::LabeledWalls(_) => ("Labeled Wall", "Labeled Walls"),
+            Self::CommonGimmicks(_) => ("Common Gimmick", "Common Gimmicks"),
+            Self::Gimmicks(_) => ("Gimmick", "Gimmicks"),
+            Self::Paths(_) => ("Path", "Paths"),
+            Self::Zones(_) => ("Zone", "Zones"),
+            Self::CourseInfos(_) => ("Course Info", "Course Infos"),
+            Self::Enemies(_) => ("Enemy", "Enemies"),
+            Self::Comments(_) => ("Comment", "Comments"),
+            Self::CommonGimmickNames(_) => ("Common Gimmick Name", "Common Gimmick Names"),
+            Self::CollisionTypes(_) => ("Collision Type", "Collision Types"),
+            Self::WallLabels(_) => ("Wall Label", "Wall Labels"),
+            Self::Bounds(_) => ("Bounds", "Bounds"),
+        };
+
+        if count == 1 { singular } else { plural }
+    }
+}
+
+/// A command that replaces `removed` with `inserted`, starting at `index` of one of the level's lists.
+/// Adding, removing and editing objects are all expressed this way.
+#[derive(Clone)]
+struct Splice {
+    index: usize,
+    removed: Objects,
+    inserted: Objects,
+}
+
+/// A single step in the history.
+pub struct HistoryEntry {
+    pub label: String,
+    splices: Vec<Splice>,
+}
+
+/// A copy of every object in the level, with nothing selected.
+#[derive(Default)]
+struct LevelSnapshot {
+    walls: Vec<Wall>,
+    labeled_walls: Vec<LabeledWall>,
+    common_gimmicks: Vec<CommonGimmick>,
+    gimmicks: Vec<Gimmick>,
+    paths: Vec<Path>,
+    zones: Vec<Zone>,
+    course_infos: Vec<CourseInfo>,
+    enemies: Vec<Enemy>,
+    comments: Vec<Comment>,
+    common_gimmick_names: Vec<String>,
+    colbin_types: Vec<String>,
+    wall_labels: Vec<String>,
+    bounds: [Point2D; 2],
+}
+
+#[derive(Default)]
+pub struct History {
+    pub steps: UndoStack<HistoryEntry>,
+    /// The level as of the last recorded step.
+    baseline: Option<LevelSnapshot>,
+    /// Overrides the generated label of the next recorded step.
+    next_label: Option<String>,
+    /// An edit was made outside of a pointer interaction, and is recorded at the end of the frame.
+    edited: bool,
+    /// The widget that had keyboard focus last frame. Text is recorded when it loses focus.
+    last_focus: Option<egui::Id>,
+    pub show_panel: bool,
+}
+
+impl History {
+    /// Names the next recorded step, for edits that are better described by what the user did.
+    pub fn label_next(&mut self, label: &str) {
+        self.next_label = Some(label.to_string());
+        self.edited = true;
+    }
+
+    /// Records the level at the end of the frame, for edits made with the keyboard.
+    pub fn mark_edited(&mut self) {
+        self.edited = true;
+    }
+}
+
+/// Finds the commands that turn `before` into `after`.
+fn diff<T: Clone + PartialEq>(
+    before: &[T],
+    after: &[T],
+    wrap: fn(Vec<T>) -> Objects,
+    splices: &mut Vec<Splice>,
+) {
+    if before.len() == after.len() {
+        // objects were edited in place
+        for (index, (old, new)) in before.iter().zip(after).enumerate() {
+            if old != new {
+                splices.push(Splice {
+                    index,
+                    removed: wrap(vec![old.clone()]),
+                    inserted: wrap(vec![new.clone()]),
+                });
+            }
+        }
+
+        return;
+    }
+
+    // objects were added or removed; everything between the unchanged start and end is replaced
+    let prefix = before
+        .iter()
+        .zip(after)
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let max_suffix = before.len().min(after.len()) - prefix;
+
+    let suffix = before
+        .iter()
+        .rev()
+        .zip(after.iter().rev())
+        .take(max_suffix)
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    splices.push(Splice {
+        index: prefix,
+        removed: wrap(before[prefix..before.len() - suffix].to_vec()),
+        inserted: wrap(after[prefix..after.len() - suffix].to_vec()),
+    });
+}
+
+/// Replaces `count` items starting at `index` with `inserted`.
+/// Name tables are tidied up outside of the history when saving, so the range is kept in bounds.
+fn replace<T: Clone>(list: &mut Vec<T>, index: usize, count: usize, inserted: &[T]) {
+    let end = (index + count).min(list.len());
+    list.splice(index.min(end)..end, inserted.iter().cloned());
+}
+
+fn make_label(splices: &[Splice]) -> String {
+    let object_splices: Vec<&Splice> = splices
+        .iter()
+        .filter(|splice| !splice.inserted.is_name_table())
+        .collect();
+
+    let Some(first) = object_splices.first() else {
+        return String::from("Edit Names");
+    };
+
+    let same_type = object_splices.iter().all(|splice| {
+        std::mem::discriminant(&splice.inserted) == std::mem::discriminant(&first.inserted)
+    });
+
+    if !same_type {
+        return String::from("Edit Objects");
+    }
+
+    let added: usize = object_splices.iter().map(|s| s.inserted.len()).sum();
+    let removed: usize = object_splices.iter().map(|s| s.removed.len()).sum();
+
+    let (action, count) = if removed == 0 {
+        ("Add", added)
+    } else if added == 0 {
+        ("Remove", removed)
+    } else {
+        ("Edit", added.max(removed))
+    };
+
+    let type_name = first.inserted.type_name(count);
+
+    if count == 1 {
+        format!("{action} {type_name}")
+    } else {
+        format!("{action} {count} {type_name}")
+    }
+}
+
+impl LevelEditor {
+    fn take_snapshot(&self) -> LevelSnapshot {
+        let mapdata = &self.current_mapdata;
+
+        let mut snapshot = LevelSnapshot {
+            walls: mapdata.walls.clone(),
+            labeled_walls: mapdata.labeled_walls.clone(),
+            common_gimmicks: mapdata.common_gimmicks.clone(),
+            gimmicks: mapdata.gimmicks.clone(),
+            paths: mapdata.paths.clone(),
+            zones: mapdata.zones.clone(),
+            course_infos: mapdata.course_infos.clone(),
+            enemies: self.current_endata.enemies.clone(),
+            comments: self.comments.clone().unwrap_or_default(),
+            common_gimmick_names: mapdata.common_gimmick_names.hex_names.clone(),
+            colbin_types: mapdata.colbin_types.names.clone(),
+            wall_labels: mapdata.wall_labels.names.clone(),
+            bounds: [mapdata.bounds_min, mapdata.bounds_max],
+        };
+
+        // selecting something isn't an edit
+        snapshot
+            .walls
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .labeled_walls
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .common_gimmicks
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .gimmicks
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .paths
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .zones
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .course_infos
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .enemies
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+        snapshot
+            .comments
+            .iter_mut()
+            .for_each(|o| o.is_selected = false);
+
+        snapshot
+    }
+
+    /// Forgets every step. This is done whenever different level data is loaded.
+    pub fn clear_history(&mut self) {
+        let show_panel = self.history.show_panel;
+
+        self.history = History {
+            show_panel,
+            ..Default::default()
+        };
+    }
+
+    /// Records any changes made since the last step, once an editing interaction has ended:
+    /// a click or drag is released, a text field loses focus, or an edit marks itself.
+    /// The level is only compared with the last step then, so that a drag becomes a single step.
+    pub fn update_history(&mut self, ctx: &egui::Context) {
+        if !self.file_open {
+            return;
+        }
+
+        if self.history.baseline.is_none() {
+            self.history.baseline = Some(self.take_snapshot());
+            return;
+        }
+
+        let focus = ctx.memory(|m| m.focused());
+        let focus_lost = self.history.last_focus.is_some() && focus != self.history.last_focus;
+        self.history.last_focus = focus;
+
+        let released = ctx.input(|i| i.pointer.any_released() && !i.pointer.any_down());
+
+        if released || focus_lost || self.history.edited {
+            self.record_changes();
+        }
+    }
+
+    /// Runs `f` without recording what it changes, e.g. name tables tidied up when saving.
+    /// Anything not yet recorded is recorded first.
+    pub(super) fn without_history<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.record_changes();
+        let result = f(self);
+        self.history.baseline = Some(self.take_snapshot());
+        result
+    }
+
+    fn record_changes(&mut self) {
+        self.history.edited = false;
+
+        let Some(before) = self.history.baseline.take() else {
+            return;
+        };
+
+        let after = self.take_snapshot();
+        let label = self.history.next_label.take();
+
+        let mut splices = Vec::new();
+
+        diff(&before.walls, &after.walls, Objects::Walls, &mut splices);
+        diff(
+            &before.labeled_walls,
+            &after.labeled_walls,
+            Objects::LabeledWalls,
+            &mut splices,
+        );
+        diff(
+            &before.common_gimmicks,
+            &after.common_gimmicks,
+            Objects::CommonGimmicks,
+            &mut splices,
+        );
+        diff(
+            &before.gimmicks,
+            &after.gimmicks,
+            Objects::Gimmicks,
+            &mut splices,
+        );
+        diff(&before.paths, &after.paths, Objects::Paths, &mut splices);
+        diff(&before.zones, &after.zones, Objects::Zones, &mut splices);
+        diff(
+            &before.course_infos,
+            &after.course_infos,
+            Objects::CourseInfos,
+            &mut splices,
+        );
+        diff(
+            &before.enemies,
+            &after.enemies,
+            Objects::Enemies,
+            &mut splices,
+        );
+        diff(
+            &before.comments,
+            &after.comments,
+            Objects::Comments,
+            &mut splices,
+        );
+        diff(
+            &before.common_gimmick_names,
+            &after.common_gimmick_names,
+            Objects::CommonGimmickNames,
+            &mut splices,
+        );
+        diff(
+            &before.colbin_types,
+            &after.colbin_types,
+            Objects::CollisionTypes,
+            &mut splices,
+        );
+        diff(
+            &before.wall_labels,
+            &after.wall_labels,
+            Objects::WallLabels,
+            &mut splices,
+        );
+        diff(
+            &[before.bounds],
+            &[after.bounds],
+            Objects::Bounds,
+            &mut splices,
+        );
+
+        self.history.baseline = Some(after);
+
+        if splices.is_empty() {
+            return;
+        }
+
+        self.history.steps.push(HistoryEntry {
+            label: label.unwrap_or_else(|| make_label(&splices)),
+            splices,
+        });
+    }
+
+    fn apply_splice(&mut self, splice: &Splice, reverse: bool) {
+        let (removed, inserted) = if reverse {
+            (&splice.inserted, &splice.removed)
+        } else {
+            (&splice.removed, &splice.inserted)
+        };
+
+        let index = splice.index;
+        let count = removed.len();
+        let mapdata = &mut self.current_mapdata;
+
+        match inserted {
+            Objects::Walls(v) => replace(&mut mapdata.walls, index, count, v),
+            Objects::LabeledWalls(v) => replace(&mut mapdata.labeled_walls, index, count, v),
+            Objects::CommonGimmicks(v) => replace(&mut mapdata.common_gimmicks, index, count, v),
+            Objects::Gimmicks(v) => replace(&mut mapdata.gimmicks, index, count, v),
+            Objects::Paths(v) => replace(&mut mapdata.paths, index, count, v),
+            Objects::Zones(v) => replace(&mut mapdata.zones, index, count, v),
+            Objects::CourseInfos(v) => replace(&mut mapdata.course_infos, index, count, v),
+            Objects::Enemies(v) => replace(&mut self.current_endata.enemies, index, count, v),
+            Objects::Comments(v) => {
+                let comments = self.comments.get_or_insert_with(Vec::new);
+                replace(comments, index, count, v);
+            }
+            Objects::CommonGimmickNames(v) => {
+                replace(&mut mapdata.common_gimmick_names.hex_names, index, count, v)
+            }
+            Objects::CollisionTypes(v) => replace(&mut mapdata.colbin_types.names, index, count, v),
+            Objects::WallLabels(v) => replace(&mut mapdata.wall_labels.names, index, count, v),
+            Objects::Bounds(v) => {
+                if let Some([min, max]) = v.first() {
+                    (mapdata.bounds_min, mapdata.bounds_max) = (*min, *max);
+                }
+            }
+        }
+    }
+
+    pub fn show_history_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.history.steps.undo_stack.is_empty(),
+                    egui::Button::new("Undo"),
+                )
+                .clicked()
+            {
+                self.undo(ui.ctx());
+            }
+
+            if ui
+                .add_enabled(
+                    !self.history.steps.redo_stack.is_empty(),
+                    egui::Button::new("Redo"),
+                )
+                .clicked()
+            {
+                self.redo(ui.ctx());
+            }
+        });
+
+        ui.separator();
+
+        // clicking a step undoes or redoes everything up to it
+        let mut target = None;
+        let undo_count = self.history.steps.undo_stack.len();
+
+        egui::ScrollArea::vertical()
+            .id_salt("le_history")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                if ui.selectable_label(undo_count == 0, "Start").clicked() {
+                    target = Some(0);
+                }
+
+                for (i, entry) in self.history.steps.undo_stack.iter().enumerate() {
+                    if ui
+                        .selectable_label(i + 1 == undo_count, &entry.label)
+                        .clicked()
+                    {
+                        target = Some(i + 1);
+                    }
+                }
+
+                for (i, entry) in self.history.steps.redo_stack.iter().rev().enumerate() {
+                    let text = egui::RichText::new(&entry.label).weak();
+
+                    if ui.selectable_label(false, text).clicked() {
+                        target = Some(undo_count + i + 1);
+                    }
+                }
+            });
+
+        if let Some(target) = target {
+            while self.history.steps.undo_stack.len() > target {
+                self.undo(ui.ctx());
+            }
+
+            while self.history.steps.undo_stack.len() < target
+                && !self.history.steps.redo_stack.is_empty()
+            {
+                self.redo(ui.ctx());
+            }
+        }
+    }
+}
+
+impl Undo for LevelEditor {
+    type Step = HistoryEntry;
+
+    fn steps(&mut self) -> &mut UndoStack<HistoryEntry> {
+        &mut self.history.steps
+    }
+
+    fn record_pending(&mut self) {
+        self.record_changes();
+    }
+
+    fn apply_step(&mut self, ctx: &egui::Context, entry: &HistoryEntry, reverse: bool) {
+        self.deselect_all();
+
+        if reverse {
+            for splice in entry.splices.iter().rev() {
+                self.apply_splice(splice, true);
+            }
+        } else {
+            for splice in entry.splices.iter() {
+                self.apply_splice(splice, false);
+            }
+        }
+
+        self.history.baseline = Some(self.take_snapshot());
+        self.load_object_textures(ctx);
+    }
+
+    fn has_history(&self) -> bool {
+        self.file_open
+    }
+}
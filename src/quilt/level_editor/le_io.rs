@@ -21,7 +21,8 @@ impl LevelEditor {
             Default::default()
         };
 
-        self.selected_object_indices.clear();
+        self.selected_objects.clear();
+        self.clear_history();
     }
 
     pub fn open_file(&mut self, ctx: &egui::Context) -> Result<()> {
@@ -84,8 +85,9 @@ impl LevelEditor {
 
             self.load_object_textures(ctx);
 
-            self.bgst_renderer.bgst_file = None;
+            self.bgst_renderer.close_file();
             self.render_bgst = false;
+            self.load_bgst_settings();
         }
 
         Ok(())
@@ -150,8 +152,9 @@ impl LevelEditor {
                 self.file_open = true;
                 self.object_textures.clear();
                 self.load_object_textures(ctx);
-                self.bgst_renderer.bgst_file = None;
+                self.bgst_renderer.close_file();
                 self.render_bgst = false;
+                self.load_bgst_settings();
             }
         }
         Ok(())
@@ -173,15 +176,19 @@ impl LevelEditor {
             }
         }
 
-        // enbin
-        if let Some(index) = self.selected_enbin_index {
-            self.archive_contents[index].1 = self.current_endata.encode();
-        }
+        // encoding tidies up the name tables, which isn't an edit
+        self.without_history(|editor| {
+            // enbin
+            if let Some(index) = editor.selected_enbin_index {
+                editor.archive_contents[index].1 = editor.current_endata.encode();
+            }
 
-        // mapbin
-        if let Some(index) = self.selected_mapbin_index {
-            self.archive_contents[index].1 = self.current_mapdata.encode();
-        }
+            // mapbin
+            if let Some(index) = editor.selected_mapbin_index {
+                editor.current_mapdata.prune_collision_types();
+                editor.archive_contents[index].1 = editor.current_mapdata.encode();
+            }
+        });
 
         // comments
         if let Some(comments) = &self.comments
@@ -225,15 +232,19 @@ impl LevelEditor {
             }
         };
 
-        // enbin
-        if let Some(index) = self.selected_enbin_index {
-            self.archive_contents[index].1 = self.current_endata.encode();
-        }
+        // encoding tidies up the name tables, which isn't an edit
+        self.without_history(|editor| {
+            // enbin
+            if let Some(index) = editor.selected_enbin_index {
+                editor.archive_contents[index].1 = editor.current_endata.encode();
+            }
 
-        // mapbin
-        if let Some(index) = self.selected_mapbin_index {
-            self.archive_contents[index].1 = self.current_mapdata.encode();
-        }
+            // mapbin
+            if let Some(index) = editor.selected_mapbin_index {
+                editor.current_mapdata.prune_collision_types();
+                editor.archive_contents[index].1 = editor.current_mapdata.encode();
+            }
+        });
 
         // comments
         if let Some(comments) = &self.comments
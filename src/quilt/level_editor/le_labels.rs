@@ -0,0 +1,261 @@
+use super::{LevelEditor, ObjectRef};
+
+use egui::Rect;
+
+const HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::from_rgb(0xFF, 0xD7, 0x00);
+
+/// Lists the labels in `wall_labels` and the labeled walls that use them.
+#[derive(Default)]
+pub struct LabelManager {
+    pub show_panel: bool,
+    /// The label being renamed, and its new name.
+    renaming: Option<(String, String)>,
+    merge_from: String,
+    merge_into: String,
+    /// Walls with this label are outlined on the canvas.
+    highlighted: Option<String>,
+}
+
+impl LevelEditor {
+    /// Every label in the level with the number of walls that use it, in name table order.
+    /// Labels used by walls but missing from the name table are listed last.
+    fn wall_label_usage(&self) -> Vec<(String, usize)> {
+        let mapdata = &self.current_mapdata;
+        let mut labels: Vec<(String, usize)> = mapdata
+            .wall_labels
+            .names
+            .iter()
+            .map(|name| (name.clone(), 0))
+            .collect();
+
+        for wall in mapdata.labeled_walls.iter() {
+            match labels.iter_mut().find(|(name, _)| *name == wall.label) {
+                Some((_, count)) => *count += 1,
+                None => labels.push((wall.label.clone(), 1)),
+            }
+        }
+
+        labels
+    }
+
+    /// Renames a label on every wall that uses it.
+    /// If `new` is already a label, the two are merged.
+    fn rename_wall_label(&mut self, old: &str, new: &str) {
+        if old == new || new.is_empty() {
+            return;
+        }
+
+        let mapdata = &mut self.current_mapdata;
+
+        for wall in mapdata.labeled_walls.iter_mut() {
+            if wall.label == old {
+                wall.label = new.to_string();
+            }
+        }
+
+        let names = &mut mapdata.wall_labels.names;
+
+        if names.iter().any(|name| name == new) {
+            names.retain(|name| name != old);
+        } else if let Some(name) = names.iter_mut().find(|name| *name == old) {
+            *name = new.to_string();
+        } else {
+            names.push(new.to_string());
+        }
+
+        if self.label_manager.highlighted.as_deref() == Some(old) {
+            self.label_manager.highlighted = Some(new.to_string());
+        }
+    }
+
+    fn select_walls_with_label(&mut self, label: &str) {
+        self.deselect_all();
+
+        let walls: Vec<ObjectRef> = self
+            .current_mapdata
+            .labeled_walls
+            .iter()
+            .filter(|w| w.label == label)
+            .map(|w| ObjectRef::LabeledWall(w.id))
+            .collect();
+
+        for wall in walls {
+            self.select_object(wall);
+        }
+    }
+
+    pub fn show_label_manager_ui(&mut self, ui: &mut egui::Ui) {
+        let labels = self.wall_label_usage();
+
+        if labels.is_empty() {
+            ui.label("This level has no wall labels.");
+            return;
+        }
+
+        let mut rename = None;
+        let mut select = None;
+        let mut delete = None;
+
+        egui::ScrollArea::vertical()
+            .id_salt("le_wall_labels")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                egui::Grid::new("le_wall_label_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Label");
+                        ui.label("Walls");
+                        ui.end_row();
+
+                        let manager = &mut self.label_manager;
+
+                        for (label, count) in labels.iter() {
+                            match &mut manager.renaming {
+                                Some((old, new)) if old == label => {
+                                    let resp =
+                                        ui.add(egui::TextEdit::singleline(new).char_limit(0x20));
+
+                                    if resp.lost_focus()
+                                        && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                                    {
+                                        rename = Some((old.clone(), new.clone()));
+                                    }
+                                }
+
+                                _ => {
+                                    ui.label(label);
+                                }
+                            }
+
+                            ui.label(count.to_string());
+
+                            ui.horizontal(|ui| {
+                                if ui.small_button("Rename").clicked() {
+                                    manager.renaming = Some((label.clone(), label.clone()));
+                                }
+
+                                if ui
+                                    .add_enabled(*count > 0, egui::Button::new("Select").small())
+                                    .clicked()
+                                {
+                                    select = Some(label.clone());
+                                }
+
+                                let is_highlighted = manager.highlighted.as_ref() == Some(label);
+
+                                if ui.selectable_label(is_highlighted, "Highlight").clicked() {
+                                    manager.highlighted = (!is_highlighted).then(|| label.clone());
+                                }
+
+                                if ui
+                                    .add_enabled(*count == 0, egui::Button::new("Delete").small())
+                                    .on_disabled_hover_text("Only unused labels can be deleted.")
+                                    .clicked()
+                                {
+                                    delete = Some(label.clone());
+                                }
+                            });
+
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        ui.separator();
+
+        let manager = &mut self.label_manager;
+        let mut merge = None;
+
+        let label_combo = |ui: &mut egui::Ui, id: &str, value: &mut String| {
+            egui::ComboBox::from_id_salt(id)
+                .selected_text(value.as_str())
+                .show_ui(ui, |ui| {
+                    for (label, _) in labels.iter() {
+                        ui.selectable_value(value, label.clone(), label);
+                    }
+                });
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Merge");
+            label_combo(ui, "le_label_merge_from", &mut manager.merge_from);
+            ui.label("into");
+            label_combo(ui, "le_label_merge_into", &mut manager.merge_into);
+
+            if ui
+                .add_enabled(
+                    !manager.merge_from.is_empty()
+                        && !manager.merge_into.is_empty()
+                        && manager.merge_from != manager.merge_into,
+                    egui::Button::new("Merge"),
+                )
+                .on_hover_text("Walls with the first label are given the second.")
+                .clicked()
+            {
+                merge = Some((manager.merge_from.clone(), manager.merge_into.clone()));
+            }
+        });
+
+        let unused = labels.iter().filter(|(_, count)| *count == 0).count();
+
+        if ui
+            .add_enabled(unused > 0, egui::Button::new("Delete unused labels"))
+            .clicked()
+        {
+            self.history.label_next("Delete Unused Labels");
+
+            let labeled_walls = &self.current_mapdata.labeled_walls;
+            self.current_mapdata
+                .wall_labels
+                .names
+                .retain(|name| labeled_walls.iter().any(|w| &w.label == name));
+        }
+
+        if let Some((old, new)) = rename {
+            self.history.label_next("Rename Label");
+            self.rename_wall_label(&old, &new);
+            self.label_manager.renaming = None;
+        }
+
+        if let Some((from, into)) = merge {
+            self.history.label_next("Merge Labels");
+            self.rename_wall_label(&from, &into);
+            self.label_manager.merge_from.clear();
+        }
+
+        if let Some(label) = select {
+            self.select_walls_with_label(&label);
+        }
+
+        if let Some(label) = delete {
+            self.history.label_next("Delete Label");
+            self.current_mapdata
+                .wall_labels
+                .names
+                .retain(|name| *name != label);
+        }
+    }
+
+    /// Outlines the walls with the highlighted label.
+    pub(super) fn draw_label_highlight(&self, ui: &egui::Ui, rect: Rect) {
+        let Some(label) = &self.label_manager.highlighted else {
+            return;
+        };
+
+        let painter = ui.painter_at(rect);
+
+        for wall in self.current_mapdata.labeled_walls.iter() {
+            if wall.label != *label {
+                continue;
+            }
+
+            let start = rect.min + self.camera.convert_to_camera(wall.start.get_vec2());
+            let end = rect.min + self.camera.convert_to_camera(wall.end.get_vec2());
+
+            painter.line_segment(
+                [start, end],
+                egui::Stroke::new(5.0_f32, HIGHLIGHT_COLOR.gamma_multiply(0.5)),
+            );
+        }
+    }
+}
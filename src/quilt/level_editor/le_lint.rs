@@ -0,0 +1,592 @@
+use super::{COLLISION_TYPES, ENEMY_LIST, LevelEditor, ObjectRef};
+
+use egui::{Rect, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    /// The level won't save correctly, or won't load in game.
+    Error,
+    /// The level saves, but probably doesn't behave as intended.
+    Warning,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "Error",
+            Self::Warning => "Warning",
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            Self::Error => egui::Color32::from_rgb(0xFF, 0x40, 0x40),
+            Self::Warning => egui::Color32::from_rgb(0xFF, 0xC0, 0x40),
+        }
+    }
+}
+
+struct Finding {
+    severity: Severity,
+    message: String,
+    object: Option<ObjectRef>,
+    /// Where the finding is, in world units. Findings about the whole level have none.
+    position: Option<Vec2>,
+}
+
+/// How the level is saved once the report is dismissed.
+#[derive(Clone, Copy)]
+pub enum SaveKind {
+    Archive { save_as: bool },
+    Folder { save_as: bool },
+}
+
+#[derive(Default)]
+pub struct LintReport {
+    pub show_panel: bool,
+    findings: Vec<Finding>,
+    analyzed: bool,
+    /// A save that was held back because the level has errors.
+    pending_save: Option<SaveKind>,
+}
+
+impl LintReport {
+    /// Shows or hides the panel. The level is checked again when it's shown.
+    pub fn toggle_panel(&mut self) {
+        self.show_panel = !self.show_panel;
+        self.analyzed = false;
+        self.pending_save = None;
+    }
+}
+
+/// The parameters of an object, by type, as `objectdata.json` slots refer to them.
+struct ParamSlots<'a> {
+    ints: &'a [i32],
+    floats: &'a [f32],
+    strings: &'a [String],
+}
+
+/// Checks a parameter against the `values` it's allowed, if it has any.
+/// Returns the parameter's value if it isn't one of them.
+fn disallowed_value(param_data: &serde_json::Value, params: &ParamSlots) -> Option<String> {
+    let values = param_data.get("values")?.as_object()?;
+    let slot = param_data.get("slot")?.as_u64()? as usize;
+
+    match param_data.get("data_type")?.as_str()? {
+        "dropdown_int" => {
+            let value = *params.ints.get(slot)?;
+            let allowed = values.values().any(|v| v.as_i64() == Some(value as i64));
+            (!allowed).then(|| value.to_string())
+        }
+
+        "dropdown_float" => {
+            let value = *params.floats.get(slot)?;
+            let allowed = values
+                .values()
+                .any(|v| v.as_f64().map(|v| v as f32) == Some(value));
+            (!allowed).then(|| value.to_string())
+        }
+
+        "dropdown_string" => {
+            let value = params.strings.get(slot)?;
+            let allowed = values.values().any(|v| v.as_str() == Some(value.as_str()));
+            (!allowed).then(|| format!("'{value}'"))
+        }
+
+        _ => None,
+    }
+}
+
+fn finding(severity: Severity, message: String, object: Option<ObjectRef>) -> Finding {
+    Finding {
+        severity,
+        message,
+        object,
+        position: None,
+    }
+}
+
+/// Checks that a string fits in the space it's saved in.
+fn length_finding(text: &str, limit: usize, what: &str, object: ObjectRef) -> Option<Finding> {
+    (text.len() > limit).then(|| {
+        finding(
+            Severity::Error,
+            format!(
+                "{what} '{text}' is {} bytes, but can only be {limit}",
+                text.len()
+            ),
+            Some(object),
+        )
+    })
+}
+
+impl LevelEditor {
+    /// The points that place each object in the level, in world units.
+    pub(super) fn object_points(&self) -> Vec<(ObjectRef, Vec<Vec2>)> {
+        let mapdata = &self.current_mapdata;
+        let mut points = Vec::new();
+
+        for wall in mapdata.walls.iter() {
+            points.push((
+                ObjectRef::Wall(wall.id),
+                vec![wall.start.get_vec2(), wall.end.get_vec2()],
+            ));
+        }
+
+        for wall in mapdata.labeled_walls.iter() {
+            points.push((
+                ObjectRef::LabeledWall(wall.id),
+                vec![wall.start.get_vec2(), wall.end.get_vec2()],
+            ));
+        }
+
+        for gmk in mapdata.common_gimmicks.iter() {
+            points.push((
+                ObjectRef::CommonGimmick(gmk.id),
+                vec![gmk.position.get_point2d().get_vec2()],
+            ));
+        }
+
+        for gmk in mapdata.gimmicks.iter() {
+            points.push((
+                ObjectRef::Gimmick(gmk.id),
+                vec![gmk.position.get_point2d().get_vec2()],
+            ));
+        }
+
+        for path in mapdata.paths.iter() {
+            points.push((
+                ObjectRef::Path(path.id),
+                path.points.iter().map(|p| p.get_vec2()).collect(),
+            ));
+        }
+
+        for zone in mapdata.zones.iter() {
+            points.push((
+                ObjectRef::Zone(zone.id),
+                vec![zone.bounds_start.get_vec2(), zone.bounds_end.get_vec2()],
+            ));
+        }
+
+        for info in mapdata.course_infos.iter() {
+            points.push((
+                ObjectRef::CourseInfo(info.id),
+                vec![info.position.get_point2d().get_vec2()],
+            ));
+        }
+
+        for enemy in self.current_endata.enemies.iter() {
+            points.push((
+                ObjectRef::Enemy(enemy.id),
+                vec![enemy.position_1.get_point2d().get_vec2()],
+            ));
+        }
+
+        points
+    }
+
+    fn lint_level(&mut self) {
+        let mut findings = Vec::new();
+        let mapdata = &self.current_mapdata;
+
+        // gimmicks the game expects a certain number of
+        let starts: Vec<_> = mapdata
+            .gimmicks
+            .iter()
+            .filter(|g| g.name == "START")
+            .collect();
+
+        match starts.as_slice() {
+            [] => findings.push(finding(
+                Severity::Error,
+                String::from("There's no START gimmick."),
+                None,
+            )),
+
+            [_] => {}
+
+            [_, duplicates @ ..] => {
+                for gmk in duplicates {
+                    findings.push(finding(
+                        Severity::Error,
+                        String::from("Duplicate START gimmick"),
+                        Some(ObjectRef::Gimmick(gmk.id)),
+                    ));
+                }
+            }
+        }
+
+        for gmk in mapdata
+            .gimmicks
+            .iter()
+            .filter(|g| g.name == "BG_BASE")
+            .skip(1)
+        {
+            findings.push(finding(
+                Severity::Warning,
+                String::from("More than one BG_BASE gimmick"),
+                Some(ObjectRef::Gimmick(gmk.id)),
+            ));
+        }
+
+        // strings longer than the space they're saved in
+        for gmk in mapdata.common_gimmicks.iter() {
+            let object = ObjectRef::CommonGimmick(gmk.id);
+            findings.extend(length_finding(
+                &gmk.params.common_string_param,
+                8,
+                "Common string",
+                object,
+            ));
+
+            for string in gmk.params.string_params.iter() {
+                findings.extend(length_finding(string, 0x40, "String parameter", object));
+            }
+        }
+
+        for gmk in mapdata.gimmicks.iter() {
+            let object = ObjectRef::Gimmick(gmk.id);
+            findings.extend(length_finding(&gmk.name, 0x30, "Name", object));
+
+            for string in gmk.params.string_params.iter() {
+                findings.extend(length_finding(string, 0x40, "String parameter", object));
+            }
+        }
+
+        for path in mapdata.paths.iter() {
+            let object = ObjectRef::Path(path.id);
+            findings.extend(length_finding(&path.name, 0x20, "Name", object));
+            findings.extend(length_finding(&path.path_type, 0x20, "Path type", object));
+
+            for string in path.params.string_params.iter() {
+                findings.extend(length_finding(string, 0x40, "String parameter", object));
+            }
+        }
+
+        for zone in mapdata.zones.iter() {
+            let object = ObjectRef::Zone(zone.id);
+            findings.extend(length_finding(&zone.name, 0x20, "Name", object));
+            findings.extend(length_finding(&zone.unk_20, 0x20, "Unknown string", object));
+
+            for string in zone.params.string_params.iter() {
+                findings.extend(length_finding(string, 0x40, "String parameter", object));
+            }
+        }
+
+        for info in mapdata.course_infos.iter() {
+            let object = ObjectRef::CourseInfo(info.id);
+            findings.extend(length_finding(&info.name, 0x20, "Name", object));
+            findings.extend(length_finding(&info.unk_20, 0x20, "Unknown string", object));
+
+            for string in info.params.string_params.iter() {
+                findings.extend(length_finding(string, 0x40, "String parameter", object));
+            }
+        }
+
+        for wall in mapdata.labeled_walls.iter() {
+            findings.extend(length_finding(
+                &wall.label,
+                0x20,
+                "Label",
+                ObjectRef::LabeledWall(wall.id),
+            ));
+        }
+
+        for enemy in self.current_endata.enemies.iter() {
+            let object = ObjectRef::Enemy(enemy.id);
+
+            for (text, limit, what) in [
+                (&enemy.name, 0x20, "Enemy ID"),
+                (&enemy.behavior, 0x20, "Behavior"),
+                (&enemy.path_name, 0x20, "Path name"),
+                (&enemy.bead_type, 0x10, "Bead type"),
+                (&enemy.bead_color, 0x10, "Bead color"),
+                (&enemy.direction, 0x08, "Direction"),
+                (&enemy.orientation, 0x10, "Orientation"),
+            ] {
+                findings.extend(length_finding(text, limit, what, object));
+            }
+
+            if !ENEMY_LIST.iter().any(|(id, _)| *id == enemy.name) {
+                findings.push(finding(
+                    Severity::Warning,
+                    format!("Unknown enemy ID '{}'", enemy.name),
+                    Some(object),
+                ));
+            }
+        }
+
+        // collision types the game doesn't know
+        let walls = mapdata
+            .walls
+            .iter()
+            .map(|w| (ObjectRef::Wall(w.id), &w.collision_type))
+            .chain(
+                mapdata
+                    .labeled_walls
+                    .iter()
+                    .map(|w| (ObjectRef::LabeledWall(w.id), &w.collision_type)),
+            );
+
+        for (object, collision_type) in walls {
+            if !COLLISION_TYPES.contains(&collision_type.as_str()) {
+                findings.push(finding(
+                    Severity::Warning,
+                    format!("Unknown collision type '{collision_type}'"),
+                    Some(object),
+                ));
+            }
+        }
+
+        // parameters outside the values objectdata.json allows
+        if let Some(json) = self.object_data_json.as_ref() {
+            let mut objects: Vec<(ObjectRef, &str, &str, ParamSlots)> = Vec::new();
+
+            for gmk in mapdata.common_gimmicks.iter() {
+                let params = &gmk.params;
+                objects.push((
+                    ObjectRef::CommonGimmick(gmk.id),
+                    "common_gimmicks",
+                    &gmk.hex,
+                    ParamSlots {
+                        ints: &params.int_params,
+                        floats: &params.float_params,
+                        strings: &params.string_params,
+                    },
+                ));
+            }
+
+            let with_params = mapdata
+                .gimmicks
+                .iter()
+                .map(|g| (ObjectRef::Gimmick(g.id), "gimmicks", &g.name, &g.params))
+                .chain(
+                    mapdata
+                        .paths
+                        .iter()
+                        .map(|p| (ObjectRef::Path(p.id), "paths", &p.name, &p.params)),
+                )
+                .chain(
+                    mapdata
+                        .zones
+                        .iter()
+                        .map(|z| (ObjectRef::Zone(z.id), "zones", &z.name, &z.params)),
+                )
+                .chain(mapdata.course_infos.iter().map(|c| {
+                    (
+                        ObjectRef::CourseInfo(c.id),
+                        "course_infos",
+                        &c.name,
+                        &c.params,
+                    )
+                }));
+
+            for (object, category, key, params) in with_params {
+                objects.push((
+                    object,
+                    category,
+                    key,
+                    ParamSlots {
+                        ints: &params.int_params,
+                        floats: &params.float_params,
+                        strings: &params.string_params,
+                    },
+                ));
+            }
+
+            for (object, category, key, params) in objects {
+                let Some(parameters) = json
+                    .get(category)
+                    .and_then(|c| c.get(key))
+                    .and_then(|o| o.get("parameters"))
+                    .and_then(|p| p.as_object())
+                else {
+                    continue;
+                };
+
+                for (param_name, param_data) in parameters {
+                    if let Some(value) = disallowed_value(param_data, &params) {
+                        findings.push(finding(
+                            Severity::Warning,
+                            format!("{param_name} is {value}, which isn't one of its values"),
+                            Some(object),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // objects outside the level
+        let (start, end) = (mapdata.bounds_min.get_vec2(), mapdata.bounds_max.get_vec2());
+        let (min, max) = (start.min(end), start.max(end));
+
+        if min.x == max.x || min.y == max.y {
+            findings.push(finding(
+                Severity::Error,
+                String::from("The level bounds are empty. Set them in Edit > Level Properties."),
+                None,
+            ));
+        } else {
+            let outside = |p: &Vec2| p.x < min.x || p.y < min.y || p.x > max.x || p.y > max.y;
+
+            for (object, points) in self.object_points() {
+                if points.iter().any(outside) {
+                    findings.push(finding(
+                        Severity::Warning,
+                        String::from("Outside the level bounds"),
+                        Some(object),
+                    ));
+                }
+            }
+        }
+
+        // findings are placed at the middle of their object
+        let object_points = self.object_points();
+
+        for finding in findings.iter_mut() {
+            finding.position = finding.object.and_then(|o| {
+                let (_, points) = object_points.iter().find(|(object, _)| *object == o)?;
+                let sum = points.iter().fold(Vec2::ZERO, |sum, p| sum + *p);
+                Some(sum / points.len().max(1) as f32)
+            });
+        }
+
+        // errors are listed first
+        findings.sort_by_key(|f| f.severity);
+
+        self.lint.findings = findings;
+        self.lint.analyzed = true;
+    }
+
+    /// Saves the level, unless it has errors, in which case the report is shown
+    /// so that they can be fixed or the level saved anyway.
+    pub(super) fn request_save(&mut self, kind: SaveKind) {
+        self.lint_level();
+
+        if self
+            .lint
+            .findings
+            .iter()
+            .any(|f| f.severity == Severity::Error)
+        {
+            self.lint.show_panel = true;
+            self.lint.pending_save = Some(kind);
+            return;
+        }
+
+        self.save(kind);
+    }
+
+    fn save(&mut self, kind: SaveKind) {
+        let result = match kind {
+            SaveKind::Archive { save_as } => self.save_file(save_as),
+            SaveKind::Folder { save_as } => self.save_folder(save_as),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Failed to save level. Reason: {:?}", e);
+        }
+    }
+
+    pub fn show_lint_ui(&mut self, ui: &mut egui::Ui) {
+        if !self.lint.analyzed {
+            self.lint_level();
+        }
+
+        if let Some(kind) = self.lint.pending_save {
+            ui.colored_label(
+                Severity::Error.color(),
+                "The level wasn't saved because it has errors.",
+            );
+
+            ui.horizontal(|ui| {
+                if ui.button("Save anyway").clicked() {
+                    self.lint.pending_save = None;
+                    self.save(kind);
+                }
+
+                if ui.button("Cancel").clicked() {
+                    self.lint.pending_save = None;
+                }
+            });
+
+            ui.separator();
+        }
+
+        if ui.button("Check").clicked() {
+            self.lint_level();
+        }
+
+        ui.separator();
+
+        if self.lint.findings.is_empty() {
+            ui.label("No problems found.");
+            return;
+        }
+
+        let count = |severity| {
+            self.lint
+                .findings
+                .iter()
+                .filter(|f| f.severity == severity)
+                .count()
+        };
+
+        ui.label(format!(
+            "{} errors, {} warnings",
+            count(Severity::Error),
+            count(Severity::Warning)
+        ));
+
+        let mut jump = None;
+
+        egui::ScrollArea::vertical()
+            .id_salt("le_lint_findings")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for (i, finding) in self.lint.findings.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(finding.severity.color(), finding.severity.name());
+
+                        let resp = ui.add_enabled(
+                            finding.position.is_some(),
+                            egui::SelectableLabel::new(false, &finding.message),
+                        );
+
+                        if resp.on_hover_text("Go to this object").clicked() {
+                            jump = Some(i);
+                        }
+                    });
+                }
+            });
+
+        if let Some(i) = jump {
+            let finding = &self.lint.findings[i];
+
+            if let Some(position) = finding.position {
+                let objects: Vec<ObjectRef> = finding.object.into_iter().collect();
+                self.jump_to(position, &objects);
+            }
+        }
+    }
+
+    /// Marks each finding on the canvas, in the color of its severity.
+    pub(super) fn draw_lint_findings(&self, ui: &egui::Ui, rect: Rect) {
+        if !self.lint.show_panel {
+            return;
+        }
+
+        let painter = ui.painter_at(rect);
+
+        for finding in self.lint.findings.iter() {
+            if let Some(position) = finding.position {
+                let pos = rect.min + self.camera.convert_to_camera(position);
+                painter.circle_stroke(
+                    pos,
+                    8.0,
+                    egui::Stroke::new(1.5_f32, finding.severity.color()),
+                );
+            }
+        }
+    }
+}
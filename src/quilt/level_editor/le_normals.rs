@@ -0,0 +1,144 @@
+use super::{
+    LevelEditor, ObjectRef,
+    le_topology::{Segment, same_point},
+};
+
+use egui::{Pos2, Rect, Vec2};
+use std::collections::VecDeque;
+
+/// The length of the normal tick drawn on each wall, in pixels.
+const NORMAL_LENGTH: f32 = 8.0;
+
+impl LevelEditor {
+    /// Draws a tick on the solid side of each shown wall, in the colour of its collision type.
+    pub(super) fn draw_wall_normals(&self, ui: &egui::Ui, rect: Rect) {
+        if !self.show_wall_normals {
+            return;
+        }
+
+        let painter = ui.painter_at(rect);
+        let mapdata = &self.current_mapdata;
+
+        let walls = mapdata
+            .walls
+            .iter()
+            .map(|w| (w.start, w.end, w.get_normalized_vector(), &w.collision_type));
+        let labeled_walls = mapdata
+            .labeled_walls
+            .iter()
+            .map(|w| (w.start, w.end, w.get_normalized_vector(), &w.collision_type));
+
+        for (start, end, normal, collision_type) in walls.chain(labeled_walls) {
+            let style = self.collision_styles.get(collision_type);
+
+            if start == end || !style.visible {
+                continue;
+            }
+
+            let start = rect.min + self.camera.convert_to_camera(start.get_vec2());
+            let end = rect.min + self.camera.convert_to_camera(end.get_vec2());
+            let mid = Pos2::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+
+            // the y axis points down on the screen
+            let tick = Vec2::new(normal.x, -normal.y) * NORMAL_LENGTH;
+
+            painter.line_segment([mid, mid + tick], egui::Stroke::new(1.5_f32, style.color()));
+        }
+    }
+
+    fn selected_walls(&self) -> Vec<ObjectRef> {
+        self.selected_objects
+            .iter()
+            .copied()
+            .filter(|o| matches!(o, ObjectRef::Wall(_) | ObjectRef::LabeledWall(_)))
+            .collect()
+    }
+
+    pub(super) fn has_selected_walls(&self) -> bool {
+        !self.selected_walls().is_empty()
+    }
+
+    /// Swaps the start and end of every selected wall, which flips its normal.
+    pub(super) fn flip_selected_walls(&mut self) {
+        self.history.label_next("Flip Walls");
+
+        for object in self.selected_walls() {
+            if let Some((start, end)) = self.wall_points_mut(object) {
+                std::mem::swap(start, end);
+            }
+        }
+    }
+
+    /// Flips walls connected to the selected walls so that each chain runs the same way,
+    /// end to start, as the first selected wall in it.
+    /// Chains stop where more than two walls meet, as there's no single way to continue.
+    pub(super) fn make_chains_consistent(&mut self) {
+        let mut segments = self.wall_segments();
+        let mut flipped = vec![false; segments.len()];
+        let mut visited = vec![false; segments.len()];
+
+        // the walls other than `index` with an endpoint at `point`
+        let neighbours = |segments: &[Segment], index: usize, point: Vec2| {
+            segments
+                .iter()
+                .enumerate()
+                .filter(|(i, s)| {
+                    *i != index && (same_point(s.start, point) || same_point(s.end, point))
+                })
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+
+        for seed in self.selected_walls() {
+            let Some(seed) = segments.iter().position(|s| s.object == seed) else {
+                continue;
+            };
+
+            if visited[seed] {
+                continue;
+            }
+
+            visited[seed] = true;
+            let mut queue = VecDeque::from([seed]);
+
+            while let Some(index) = queue.pop_front() {
+                let (start, end) = (segments[index].start, segments[index].end);
+
+                // the next wall should start where this one ends, and the previous should end
+                // where this one starts
+                for (point, next_starts_here) in [(end, true), (start, false)] {
+                    let [next] = neighbours(&segments, index, point)[..] else {
+                        continue;
+                    };
+
+                    if visited[next] {
+                        continue;
+                    }
+
+                    let segment = &mut segments[next];
+                    let starts_here = same_point(segment.start, point);
+
+                    if starts_here != next_starts_here {
+                        std::mem::swap(&mut segment.start, &mut segment.end);
+                        flipped[next] = true;
+                    }
+
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if !flipped.contains(&true) {
+            return;
+        }
+
+        self.history.label_next("Make Chains Consistent");
+
+        for (segment, _) in segments.iter().zip(flipped).filter(|(_, f)| *f) {
+            if let Some((start, end)) = self.wall_points_mut(segment.object) {
+                std::mem::swap(start, end);
+            }
+        }
+    }
+}
@@ -2,30 +2,36 @@ use anyhow::{Context, Result};
 
 use egui::{self, Color32, Rect};
 
-use super::{COLLISION_TYPES, EditMode, LevelEditor, ObjectIndex, Params};
+use super::{COLLISION_TYPES, EditMode, LevelEditor, ObjectRef, Params};
 
 use super::{ENEMY_LIST, color_string_to_label, enemy_id_to_name, label_to_color_string};
 
+use super::{le_path, le_selection::Translate, le_snap::drag_position, le_zone};
+
+use crate::quilt::common::Point2D;
+
 // const WALL_COLOR: Color32 = egui::Color32::from_rgb(
 //     0xF5, 0x8A, 0x07
 // );
 
-const GIMMICK_COLOR: Color32 = egui::Color32::from_rgb(0xF8, 0x33, 0x3C);
+pub(super) const GIMMICK_COLOR: Color32 = egui::Color32::from_rgb(0xF8, 0x33, 0x3C);
+
+pub(super) const PATH_COLOR: Color32 = egui::Color32::from_rgb(0x44, 0xAF, 0x69);
 
-const PATH_COLOR: Color32 = egui::Color32::from_rgb(0x44, 0xAF, 0x69);
+pub(super) const COMMON_GIMMICK_COLOR: Color32 = egui::Color32::from_rgb(0xFC, 0xAB, 0x10);
 
-const COMMON_GIMMICK_COLOR: Color32 = egui::Color32::from_rgb(0xFC, 0xAB, 0x10);
+pub(super) const ZONE_COLOR: Color32 = egui::Color32::from_rgb(0x2B, 0x9E, 0xB3);
 
-const ZONE_COLOR: Color32 = egui::Color32::from_rgb(0x2B, 0x9E, 0xB3);
+pub(super) const COURSE_INFO_COLOR: Color32 = egui::Color32::from_rgb(0xEA, 0x8C, 0x55);
 
-const COURSE_INFO_COLOR: Color32 = egui::Color32::from_rgb(0xEA, 0x8C, 0x55);
+pub(super) const COMMENT_COLOR: Color32 = egui::Color32::from_rgb(0x77, 0x77, 0x77);
 
-const COMMENT_COLOR: Color32 = egui::Color32::from_rgb(0x77, 0x77, 0x77);
+pub(super) const ENEMY_COLOR: Color32 = egui::Color32::from_rgb(0xE3, 0x96, 0xDF);
 
 pub const SQUARE_SIZE: f32 = 2.0;
 
 const BIG_CIRCLE_RADIUS: f32 = 0.5;
-const SMALL_CIRCLE_RADIUS: f32 = 0.1;
+pub(super) const SMALL_CIRCLE_RADIUS: f32 = 0.1;
 
 #[derive(PartialEq)]
 enum DataType {
@@ -48,7 +54,21 @@ impl LevelEditor {
     /* object rendering */
     pub fn update_walls(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
-        for (index, wall) in self.current_mapdata.walls.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        let vertices = self.wall_vertices();
+        let lock_angle = ui.ctx().input(|i| i.modifiers.shift);
+        let is_painting = self.collision_brush.active;
+
+        for wall in self.current_mapdata.walls.iter_mut() {
+            let style = self.collision_styles.get(&wall.collision_type);
+
+            if !style.visible {
+                continue;
+            }
+
             let start = canvas_rect.min + self.camera.convert_to_camera(wall.start.get_vec2());
             let end = canvas_rect.min + self.camera.convert_to_camera(wall.end.get_vec2());
 
@@ -62,62 +82,105 @@ impl LevelEditor {
                 egui::Vec2::splat(SMALL_CIRCLE_RADIUS * self.camera.zoom),
             );
 
-            let start_resp = ui.interact(
-                canvas_rect.intersect(start_rect),
-                egui::Id::new(&wall.start as *const _),
-                egui::Sense::click_and_drag(),
-            );
-
-            let end_resp = ui.interact(
-                canvas_rect.intersect(end_rect),
-                egui::Id::new(&wall.end as *const _),
-                egui::Sense::click_and_drag(),
-            );
-
             let color = if wall.is_selected {
                 egui::Color32::LIGHT_BLUE
             } else {
                 egui::Color32::WHITE
             };
 
-            painter.line_segment(
-                [start, end],
-                egui::Stroke::new(1.0_f32, egui::Color32::WHITE),
-            );
+            style.draw_line(&painter, [start, end], style.color());
 
-            if !matches!(self.wall_edit_mode, EditMode::Edit) {
+            if !matches!(self.wall_edit_mode, EditMode::Edit) || style.locked || is_painting {
                 continue;
             }
 
+            let start_resp = ui.interact(
+                canvas_rect.intersect(start_rect),
+                egui::Id::new((wall.id, "start")),
+                egui::Sense::click_and_drag(),
+            );
+
+            let end_resp = ui.interact(
+                canvas_rect.intersect(end_rect),
+                egui::Id::new((wall.id, "end")),
+                egui::Sense::click_and_drag(),
+            );
+
             painter.circle_filled(start, SMALL_CIRCLE_RADIUS * self.camera.zoom, color);
             painter.circle_filled(end, SMALL_CIRCLE_RADIUS * self.camera.zoom, color);
 
             let mut clicked = false;
             if start_resp.clicked() {
                 clicked = true;
-            } else if start_resp.dragged() {
-                let world_delta = start_resp.drag_delta() / self.camera.zoom;
-                wall.start.x += world_delta.x;
-                wall.start.y -= world_delta.y;
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &start_resp,
+                &self.camera,
+                canvas_rect,
+                wall.start.get_vec2(),
+            ) {
+                if wall.is_selected && is_multi_selected {
+                    selection_delta = Some(self.snap.snap_point(pos) - wall.start.get_vec2());
+                } else {
+                    wall.start = Point2D::from_vec2(self.snap.snap_endpoint(
+                        pos,
+                        wall.end.get_vec2(),
+                        lock_angle,
+                        &vertices,
+                        Some(wall.id),
+                        self.camera.zoom,
+                    ));
+                }
             }
 
             if end_resp.clicked() {
                 clicked = true;
-            } else if end_resp.dragged() {
-                let world_delta = end_resp.drag_delta() / self.camera.zoom;
-                wall.end.x += world_delta.x;
-                wall.end.y -= world_delta.y;
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &end_resp,
+                &self.camera,
+                canvas_rect,
+                wall.end.get_vec2(),
+            ) {
+                if wall.is_selected && is_multi_selected {
+                    selection_delta = Some(self.snap.snap_point(pos) - wall.end.get_vec2());
+                } else {
+                    wall.end = Point2D::from_vec2(self.snap.snap_endpoint(
+                        pos,
+                        wall.start.get_vec2(),
+                        lock_angle,
+                        &vertices,
+                        Some(wall.id),
+                        self.camera.zoom,
+                    ));
+                }
             }
 
             if clicked {
-                self.selected_object_indices.push(ObjectIndex::Wall(index));
+                clicked_object = Some(ObjectRef::Wall(wall.id));
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_labeled_walls(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
-        for (index, wall) in self.current_mapdata.labeled_walls.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        let vertices = self.wall_vertices();
+        let lock_angle = ui.ctx().input(|i| i.modifiers.shift);
+        let is_painting = self.collision_brush.active;
+
+        for wall in self.current_mapdata.labeled_walls.iter_mut() {
+            let style = self.collision_styles.get(&wall.collision_type);
+
+            if !style.visible {
+                continue;
+            }
+
             let start = canvas_rect.min + self.camera.convert_to_camera(wall.start.get_vec2());
             let end = canvas_rect.min + self.camera.convert_to_camera(wall.end.get_vec2());
 
@@ -131,63 +194,101 @@ impl LevelEditor {
                 egui::Vec2::splat(SMALL_CIRCLE_RADIUS * self.camera.zoom),
             );
 
-            let start_resp = ui.interact(
-                canvas_rect.intersect(start_rect),
-                egui::Id::new(&wall.start as *const _),
-                egui::Sense::click_and_drag(),
-            );
-
-            let end_resp = ui.interact(
-                canvas_rect.intersect(end_rect),
-                egui::Id::new(&wall.end as *const _),
-                egui::Sense::click_and_drag(),
-            );
-
             let color = if wall.is_selected {
                 egui::Color32::RED
             } else {
                 egui::Color32::WHITE
             };
 
+            // labeled walls are outlined to tell them apart from walls of the same type
             painter.line_segment(
                 [start, end],
-                egui::Stroke::new(1.0_f32, egui::Color32::LIGHT_RED),
+                egui::Stroke::new(4.0_f32, egui::Color32::LIGHT_RED.gamma_multiply(0.5)),
             );
+            style.draw_line(&painter, [start, end], style.color());
 
-            if !matches!(self.labeled_wall_edit_mode, EditMode::Edit) {
+            if !matches!(self.labeled_wall_edit_mode, EditMode::Edit) || style.locked || is_painting
+            {
                 continue;
             }
 
+            let start_resp = ui.interact(
+                canvas_rect.intersect(start_rect),
+                egui::Id::new((wall.id, "start")),
+                egui::Sense::click_and_drag(),
+            );
+
+            let end_resp = ui.interact(
+                canvas_rect.intersect(end_rect),
+                egui::Id::new((wall.id, "end")),
+                egui::Sense::click_and_drag(),
+            );
+
             painter.circle_filled(start, SMALL_CIRCLE_RADIUS * self.camera.zoom, color);
             painter.circle_filled(end, SMALL_CIRCLE_RADIUS * self.camera.zoom, color);
 
             let mut clicked = false;
             if start_resp.clicked() {
                 clicked = true;
-            } else if start_resp.dragged() {
-                let world_delta = start_resp.drag_delta() / self.camera.zoom;
-                wall.start.x += world_delta.x;
-                wall.start.y -= world_delta.y;
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &start_resp,
+                &self.camera,
+                canvas_rect,
+                wall.start.get_vec2(),
+            ) {
+                if wall.is_selected && is_multi_selected {
+                    selection_delta = Some(self.snap.snap_point(pos) - wall.start.get_vec2());
+                } else {
+                    wall.start = Point2D::from_vec2(self.snap.snap_endpoint(
+                        pos,
+                        wall.end.get_vec2(),
+                        lock_angle,
+                        &vertices,
+                        Some(wall.id),
+                        self.camera.zoom,
+                    ));
+                }
             }
 
             if end_resp.clicked() {
                 clicked = true;
-            } else if end_resp.dragged() {
-                let world_delta = end_resp.drag_delta() / self.camera.zoom;
-                wall.end.x += world_delta.x;
-                wall.end.y -= world_delta.y;
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &end_resp,
+                &self.camera,
+                canvas_rect,
+                wall.end.get_vec2(),
+            ) {
+                if wall.is_selected && is_multi_selected {
+                    selection_delta = Some(self.snap.snap_point(pos) - wall.end.get_vec2());
+                } else {
+                    wall.end = Point2D::from_vec2(self.snap.snap_endpoint(
+                        pos,
+                        wall.start.get_vec2(),
+                        lock_angle,
+                        &vertices,
+                        Some(wall.id),
+                        self.camera.zoom,
+                    ));
+                }
             }
 
             if clicked {
-                self.selected_object_indices
-                    .push(ObjectIndex::LabeledWall(index));
+                clicked_object = Some(ObjectRef::LabeledWall(wall.id));
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_common_gimmicks(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) -> Result<()> {
         let painter = ui.painter_at(canvas_rect);
-        for (index, gmk) in self.current_mapdata.common_gimmicks.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for gmk in self.current_mapdata.common_gimmicks.iter_mut() {
             if &gmk.hex == "NONE" && !self.display_none {
                 continue;
             }
@@ -207,7 +308,7 @@ impl LevelEditor {
 
             let resp = ui.interact(
                 canvas_rect.intersect(square),
-                egui::Id::new(gmk as *const _),
+                egui::Id::new(gmk.id),
                 egui::Sense::click_and_drag(),
             );
 
@@ -267,22 +368,37 @@ impl LevelEditor {
             }
 
             if resp.clicked() {
-                self.selected_object_indices
-                    .push(ObjectIndex::CommonGimmick(index));
-            } else if resp.dragged() {
-                let world_delta = resp.drag_delta() / self.camera.zoom;
-
-                gmk.position.x += world_delta.x;
-                gmk.position.y -= world_delta.y;
+                clicked_object = Some(ObjectRef::CommonGimmick(gmk.id));
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &resp,
+                &self.camera,
+                canvas_rect,
+                gmk.position.get_point2d().get_vec2(),
+            ) {
+                let target = self.snap.snap_point(pos);
+
+                if gmk.is_selected && is_multi_selected {
+                    selection_delta = Some(target - gmk.position.get_point2d().get_vec2());
+                } else {
+                    gmk.position.x = target.x;
+                    gmk.position.y = target.y;
+                }
             }
         }
 
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
+
         Ok(())
     }
 
     pub fn update_gimmicks(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
-        for (index, gmk) in self.current_mapdata.gimmicks.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for gmk in self.current_mapdata.gimmicks.iter_mut() {
             if &gmk.name == "NONE" && !self.display_none {
                 continue;
             }
@@ -314,7 +430,7 @@ impl LevelEditor {
 
             let resp = ui.interact(
                 canvas_rect.intersect(square),
-                egui::Id::new(gmk as *const _),
+                egui::Id::new(gmk.id),
                 egui::Sense::click_and_drag(),
             );
 
@@ -358,21 +474,36 @@ impl LevelEditor {
             }
 
             if resp.clicked() {
-                self.selected_object_indices
-                    .push(ObjectIndex::Gimmick(index));
-            } else if resp.dragged() {
-                let world_delta = resp.drag_delta() / self.camera.zoom;
-
-                gmk.position.x += world_delta.x;
-                gmk.position.y -= world_delta.y;
+                clicked_object = Some(ObjectRef::Gimmick(gmk.id));
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &resp,
+                &self.camera,
+                canvas_rect,
+                gmk.position.get_point2d().get_vec2(),
+            ) {
+                let target = self.snap.snap_point(pos);
+
+                if gmk.is_selected && is_multi_selected {
+                    selection_delta = Some(target - gmk.position.get_point2d().get_vec2());
+                } else {
+                    gmk.position.x = target.x;
+                    gmk.position.y = target.y;
+                }
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_paths(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
 
-        for (index, path) in self.current_mapdata.paths.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for path in self.current_mapdata.paths.iter_mut() {
             if &path.name == "NONE" && !self.display_none {
                 continue;
             }
@@ -410,46 +541,70 @@ impl LevelEditor {
 
                 let start_resp = ui.interact(
                     canvas_rect.intersect(start_rect),
-                    egui::Id::new(format!("path-{}-{}-start-{}", index, i, i % 2,)),
+                    egui::Id::new((path.id, i, "start")),
                     egui::Sense::click_and_drag(),
                 );
 
                 let end_resp = ui.interact(
                     canvas_rect.intersect(end_rect),
-                    egui::Id::new(format!("path-{}-{}-end-{}", index, i, i % 2)),
+                    egui::Id::new((path.id, i, "end")),
                     egui::Sense::click_and_drag(),
                 );
 
                 let mut clicked = false;
                 if start_resp.clicked() {
                     clicked = true;
-                } else if start_resp.dragged() {
-                    let world_delta = start_resp.drag_delta() / self.camera.zoom;
-                    let start = &mut path.points[i];
-                    start.x += world_delta.x;
-                    start.y -= world_delta.y;
+                } else if let Some(pos) = drag_position(
+                    ui.ctx(),
+                    &start_resp,
+                    &self.camera,
+                    canvas_rect,
+                    path.points[i].get_vec2(),
+                ) {
+                    let target = self.snap.snap_point(pos);
+
+                    if path.is_selected && is_multi_selected {
+                        selection_delta = Some(target - path.points[i].get_vec2());
+                    } else {
+                        path.points[i] = Point2D::from_vec2(target);
+                    }
                 }
 
                 if end_resp.clicked() {
                     clicked = true;
-                } else if end_resp.dragged() {
-                    let world_delta = end_resp.drag_delta() / self.camera.zoom;
-                    let end = &mut path.points[i + 1];
-                    end.x += world_delta.x;
-                    end.y -= world_delta.y;
+                } else if let Some(pos) = drag_position(
+                    ui.ctx(),
+                    &end_resp,
+                    &self.camera,
+                    canvas_rect,
+                    path.points[i + 1].get_vec2(),
+                ) {
+                    let target = self.snap.snap_point(pos);
+
+                    if path.is_selected && is_multi_selected {
+                        selection_delta = Some(target - path.points[i + 1].get_vec2());
+                    } else {
+                        path.points[i + 1] = Point2D::from_vec2(target);
+                    }
                 }
 
                 if clicked {
-                    self.selected_object_indices.push(ObjectIndex::Path(index));
+                    clicked_object = Some(ObjectRef::Path(path.id));
                 }
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_zones(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
 
-        for (index, zone) in self.current_mapdata.zones.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for zone in self.current_mapdata.zones.iter_mut() {
             if &zone.name == "NONE" && !self.display_none {
                 continue;
             }
@@ -463,7 +618,7 @@ impl LevelEditor {
 
             let body_resp = ui.interact(
                 canvas_rect.intersect(square),
-                egui::Id::new(zone as *const _),
+                egui::Id::new(zone.id),
                 egui::Sense::click_and_drag(),
             );
 
@@ -481,74 +636,50 @@ impl LevelEditor {
                 egui::Stroke::new(1.0_f32, ZONE_COLOR),
             );
 
+            le_zone::draw_zone_name(&painter, zone, square, ZONE_COLOR);
+
             if !matches!(self.zone_edit_mode, EditMode::Edit) {
                 continue;
             }
 
             if body_resp.clicked() {
-                self.selected_object_indices.push(ObjectIndex::Zone(index));
-            } else if body_resp.dragged() {
-                let world_delta = body_resp.drag_delta() / self.camera.zoom;
-
-                zone.bounds_start.x += world_delta.x;
-                zone.bounds_start.y -= world_delta.y;
-                zone.bounds_end.x += world_delta.x;
-                zone.bounds_end.y -= world_delta.y;
+                clicked_object = Some(ObjectRef::Zone(zone.id));
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &body_resp,
+                &self.camera,
+                canvas_rect,
+                zone.bounds_start.get_vec2(),
+            ) {
+                // the zone moves with its start corner, which is what snaps
+                let delta = self.snap.snap_point(pos) - zone.bounds_start.get_vec2();
+
+                if zone.is_selected && is_multi_selected {
+                    selection_delta = Some(delta);
+                } else {
+                    zone.bounds_start = Point2D::from_vec2(zone.bounds_start.get_vec2() + delta);
+                    zone.bounds_end = Point2D::from_vec2(zone.bounds_end.get_vec2() + delta);
+                }
             }
 
             if !zone.is_selected {
                 continue;
             }
 
-            let start_rect = egui::Rect::from_center_size(
-                egui::Pos2::new(start.x, start.y - SMALL_CIRCLE_RADIUS * 2.0),
-                egui::Vec2::splat(SMALL_CIRCLE_RADIUS * self.camera.zoom),
-            );
-
-            let end_rect = egui::Rect::from_center_size(
-                egui::Pos2::new(end.x, end.y - SMALL_CIRCLE_RADIUS * 2.0),
-                egui::Vec2::splat(SMALL_CIRCLE_RADIUS * self.camera.zoom),
-            );
-
-            let color = egui::Color32::from_rgb(0x00, 0x9F, 0xFD);
-
-            let radius = SMALL_CIRCLE_RADIUS * 2.0;
-
-            painter.circle_filled(start, radius * self.camera.zoom, color);
-            painter.circle_filled(end, radius * self.camera.zoom, color);
-
-            let start_resp = ui.interact(
-                start_rect,
-                egui::Id::new(&zone.bounds_start as *const _),
-                egui::Sense::click_and_drag(),
-            );
-
-            let end_resp = ui.interact(
-                end_rect,
-                egui::Id::new(&zone.bounds_end as *const _),
-                egui::Sense::click_and_drag(),
-            );
-
-            if start_resp.dragged() {
-                let world_delta = start_resp.drag_delta() / self.camera.zoom;
-
-                zone.bounds_start.x += world_delta.x;
-                zone.bounds_start.y -= world_delta.y;
-            }
-
-            if end_resp.dragged() {
-                let world_delta = end_resp.drag_delta() / self.camera.zoom;
-
-                zone.bounds_end.x += world_delta.x;
-                zone.bounds_end.y -= world_delta.y;
-            }
+            le_zone::process_zone_handles(ui, canvas_rect, &self.camera, &self.snap, zone);
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_course_info(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
 
-        for (index, info) in self.current_mapdata.course_infos.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for info in self.current_mapdata.course_infos.iter_mut() {
             if &info.name == "NONE" && !self.display_none {
                 continue;
             }
@@ -577,7 +708,7 @@ impl LevelEditor {
 
             let resp = ui.interact(
                 canvas_rect.intersect(square),
-                egui::Id::new(info as *const _),
+                egui::Id::new(info.id),
                 egui::Sense::click_and_drag(),
             );
 
@@ -592,21 +723,36 @@ impl LevelEditor {
             }
 
             if resp.clicked() {
-                self.selected_object_indices
-                    .push(ObjectIndex::CourseInfo(index));
-            } else if resp.dragged() {
-                let world_delta = resp.drag_delta() / self.camera.zoom;
-
-                info.position.x += world_delta.x;
-                info.position.y -= world_delta.y;
+                clicked_object = Some(ObjectRef::CourseInfo(info.id));
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &resp,
+                &self.camera,
+                canvas_rect,
+                info.position.get_point2d().get_vec2(),
+            ) {
+                let target = self.snap.snap_point(pos);
+
+                if info.is_selected && is_multi_selected {
+                    selection_delta = Some(target - info.position.get_point2d().get_vec2());
+                } else {
+                    info.position.x = target.x;
+                    info.position.y = target.y;
+                }
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_enemies(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
         let painter = ui.painter_at(canvas_rect);
 
-        for (index, enemy) in self.current_endata.enemies.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for enemy in self.current_endata.enemies.iter_mut() {
             let pos = enemy.position_1.get_point2d();
             let screen_pos =
                 canvas_rect.min.to_vec2() + self.camera.convert_to_camera(pos.get_vec2());
@@ -621,13 +767,11 @@ impl LevelEditor {
 
             let resp = ui.interact(
                 canvas_rect.intersect(square),
-                egui::Id::new(enemy as *const _),
+                egui::Id::new(enemy.id),
                 egui::Sense::click_and_drag(),
             );
 
-            let color = egui::Color32::from_rgb(0xE3, 0x96, 0xDF);
-
-            painter.rect_stroke(square, 0.0, egui::Stroke::new(1.0_f32, color));
+            painter.rect_stroke(square, 0.0, egui::Stroke::new(1.0_f32, ENEMY_COLOR));
 
             if resp.hovered() {
                 painter.text(
@@ -640,14 +784,26 @@ impl LevelEditor {
             }
 
             if resp.clicked() {
-                self.selected_object_indices.push(ObjectIndex::Enemy(index));
-            } else if resp.dragged() {
-                let world_delta = resp.drag_delta() / self.camera.zoom;
-
-                enemy.position_1.x += world_delta.x;
-                enemy.position_1.y -= world_delta.y;
+                clicked_object = Some(ObjectRef::Enemy(enemy.id));
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &resp,
+                &self.camera,
+                canvas_rect,
+                enemy.position_1.get_point2d().get_vec2(),
+            ) {
+                let target = self.snap.snap_point(pos);
+                let delta = target - enemy.position_1.get_point2d().get_vec2();
+
+                if enemy.is_selected && is_multi_selected {
+                    selection_delta = Some(delta);
+                } else {
+                    enemy.translate(delta);
+                }
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     pub fn update_comments(&mut self, ui: &mut egui::Ui, canvas_rect: Rect) {
@@ -656,7 +812,11 @@ impl LevelEditor {
         // it's already been checked against None
         let comments = self.comments.as_mut().unwrap();
 
-        for (index, comment) in comments.iter_mut().enumerate() {
+        let is_multi_selected = self.selected_objects.len() > 1;
+        let mut clicked_object = None;
+        let mut selection_delta = None;
+
+        for comment in comments.iter_mut() {
             // check if this comment should even be processed
             let current_filename = &self.archive_contents[self.selected_file_index].0;
 
@@ -673,7 +833,7 @@ impl LevelEditor {
 
             let resp = ui.interact(
                 canvas_rect.intersect(rect),
-                egui::Id::new(&comment as *const _),
+                egui::Id::new(comment.id),
                 egui::Sense::click_and_drag(),
             );
 
@@ -697,24 +857,36 @@ impl LevelEditor {
 
             if resp.clicked() {
                 clicked = true;
-            } else if resp.dragged() {
-                let world_delta = resp.drag_delta() / self.camera.zoom;
-                comment.position.x += world_delta.x;
-                comment.position.y -= world_delta.y;
+            } else if let Some(pos) = drag_position(
+                ui.ctx(),
+                &resp,
+                &self.camera,
+                canvas_rect,
+                comment.position.get_vec2(),
+            ) {
+                let target = self.snap.snap_point(pos);
+
+                if comment.is_selected && is_multi_selected {
+                    selection_delta = Some(target - comment.position.get_vec2());
+                } else {
+                    comment.position = Point2D::from_vec2(target);
+                }
             }
 
             if clicked {
-                self.selected_object_indices
-                    .push(ObjectIndex::Comment(index));
+                clicked_object = Some(ObjectRef::Comment(comment.id));
             }
         }
+
+        self.apply_object_interaction(ui.ctx(), clicked_object, selection_delta);
     }
 
     /* object attributes */
     pub fn process_wall_attributes(&mut self, ui: &mut egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.walls.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -786,8 +958,9 @@ impl LevelEditor {
 
     pub fn process_labeled_wall_attributes(&mut self, ui: &egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.labeled_walls.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -950,8 +1123,9 @@ impl LevelEditor {
         index: usize,
     ) -> Result<()> {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.common_gimmicks.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return Ok(());
         }
 
@@ -1590,8 +1764,9 @@ impl LevelEditor {
 
     pub fn process_gimmick_attributes(&mut self, ui: &mut egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.gimmicks.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -1655,8 +1830,9 @@ impl LevelEditor {
 
     pub fn process_path_attributes(&mut self, ui: &mut egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.paths.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -1668,6 +1844,8 @@ impl LevelEditor {
         let path = &mut self.current_mapdata.paths[index];
         path.is_selected = true;
 
+        let mut renamed = None;
+
         egui::Area::new(egui::Id::from("le_path_attribute_editor"))
             .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 10.0))
             .show(ui.ctx(), |ui| {
@@ -1677,22 +1855,34 @@ impl LevelEditor {
                         ui.label("Edit path attributes");
 
                         ui.label("Name");
-                        ui.add(egui::TextEdit::singleline(&mut path.name).char_limit(0x20));
+                        let name_resp =
+                            ui.add(egui::TextEdit::singleline(&mut path.name).char_limit(0x20));
 
-                        if let Some(data) = self.object_data_json.as_ref() {
-                            if let Some(path_data) = data.get(&path.name) {
-                                if let Some(desc) =
-                                    path_data.get("description").and_then(|d| d.as_str())
-                                    && !desc.is_empty()
-                                {
-                                    ui.label(desc);
-                                }
+                        // enemies refer to paths by name, so they may need updating after a rename
+                        if name_resp.gained_focus() {
+                            ui.data_mut(|d| d.insert_temp(name_resp.id, path.name.clone()));
+                        }
 
-                                if let Some(note) = path_data.get("note").and_then(|n| n.as_str())
-                                    && !note.is_empty()
-                                {
-                                    ui.label(format!("Note: {note}"));
-                                }
+                        if name_resp.lost_focus()
+                            && let Some(old) = ui.data(|d| d.get_temp::<String>(name_resp.id))
+                        {
+                            renamed = Some((old, path.name.clone()));
+                        }
+
+                        if let Some(data) = self.object_data_json.as_ref()
+                            && let Some(path_data) = data.get(&path.name)
+                        {
+                            if let Some(desc) =
+                                path_data.get("description").and_then(|d| d.as_str())
+                                && !desc.is_empty()
+                            {
+                                ui.label(desc);
+                            }
+
+                            if let Some(note) = path_data.get("note").and_then(|n| n.as_str())
+                                && !note.is_empty()
+                            {
+                                ui.label(format!("Note: {note}"));
                             }
                         }
 
@@ -1753,6 +1943,42 @@ impl LevelEditor {
                                     }
                                 },
                             );
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Reverse").clicked() {
+                                    path.points.reverse();
+                                }
+
+                                if ui
+                                    .button("Smooth")
+                                    .on_hover_text("Moves each point towards its neighbours. The ends stay in place.")
+                                    .clicked()
+                                {
+                                    path.points = le_path::smooth(&path.points);
+                                }
+                            });
+
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button("Resample")
+                                    .on_hover_text("Replaces the points with evenly spaced ones along the path.")
+                                    .clicked()
+                                {
+                                    path.points = le_path::resample(
+                                        &path.points,
+                                        self.path_tools.resample_spacing,
+                                    );
+                                }
+
+                                ui.label("Spacing");
+                                ui.add(
+                                    egui::DragValue::new(&mut self.path_tools.resample_spacing)
+                                        .speed(0.1)
+                                        .range(0.1..=100.0),
+                                );
+                            });
+
+                            ui.label("On the canvas, click a handle between points to insert one, or past an end to extend the path. Alt+click a point to delete it.");
                         });
 
                         Self::process_mapdata_parameters(
@@ -1768,12 +1994,17 @@ impl LevelEditor {
                         Ok::<(), anyhow::Error>(())
                     });
             });
+
+        if let Some((old, new)) = renamed {
+            self.offer_path_rename(old, new);
+        }
     }
 
     pub fn process_zone_attributes(&mut self, ui: &mut egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.zones.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -1873,8 +2104,9 @@ impl LevelEditor {
 
     pub fn process_course_info_attributes(&mut self, ui: &mut egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_mapdata.course_infos.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -1940,8 +2172,9 @@ impl LevelEditor {
 
     pub fn process_enemy_attributes(&mut self, ui: &mut egui::Ui, index: usize) {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
             self.current_endata.enemies.remove(index);
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
@@ -1950,6 +2183,14 @@ impl LevelEditor {
             return;
         }
 
+        let mut path_names: Vec<String> = Vec::new();
+
+        for path in self.current_mapdata.paths.iter() {
+            if !path_names.contains(&path.name) {
+                path_names.push(path.name.clone());
+            }
+        }
+
         let enemy = &mut self.current_endata.enemies[index];
 
         enemy.is_selected = true;
@@ -2004,12 +2245,31 @@ impl LevelEditor {
                                 }
                             });
 
-                        ui.horizontal(|ui| {
-                            ui.label("Path name");
-                            ui.add(
-                                egui::TextEdit::singleline(&mut enemy.path_name).char_limit(0x20),
+                        egui::ComboBox::from_label("Path")
+                            .selected_text(if enemy.path_name.is_empty() {
+                                "(none)"
+                            } else {
+                                &enemy.path_name
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut enemy.path_name, String::new(), "(none)");
+
+                                for name in path_names.iter() {
+                                    ui.selectable_value(&mut enemy.path_name, name.clone(), name);
+                                }
+                            });
+
+                        ui.add(egui::TextEdit::singleline(&mut enemy.path_name).char_limit(0x20));
+
+                        if !enemy.path_name.is_empty() && !path_names.contains(&enemy.path_name) {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!(
+                                    "There's no path named '{}' in this level.",
+                                    enemy.path_name
+                                ),
                             );
-                        });
+                        }
 
                         egui::ComboBox::from_label("Bead type")
                             .selected_text(&enemy.bead_type)
@@ -2058,6 +2318,40 @@ impl LevelEditor {
                             ui.add(egui::TextEdit::singleline(&mut enemy.unk_88).char_limit(8));
                         });
 
+                        ui.collapsing("Positions", |ui| {
+                            let positions = [
+                                ("Main", &mut enemy.position_1),
+                                ("Position 2", &mut enemy.position_2),
+                                ("Position 3", &mut enemy.position_3),
+                            ];
+
+                            for (text, position) in positions {
+                                ui.label(text);
+                                ui.horizontal(|ui| {
+                                    ui.label("X");
+                                    ui.add(
+                                        egui::DragValue::new(&mut position.x)
+                                            .speed(0.5)
+                                            .range(f32::MIN..=f32::MAX),
+                                    );
+                                    ui.label("Y");
+                                    ui.add(
+                                        egui::DragValue::new(&mut position.y)
+                                            .speed(0.5)
+                                            .range(f32::MIN..=f32::MAX),
+                                    );
+                                    ui.label("Z");
+                                    ui.add(
+                                        egui::DragValue::new(&mut position.z)
+                                            .speed(0.5)
+                                            .range(f32::MIN..=f32::MAX),
+                                    );
+                                });
+                            }
+
+                            ui.label("Positions 2 and 3 can also be dragged on the canvas.");
+                        });
+
                         egui::ComboBox::from_label("Orientation")
                             .selected_text(&enemy.orientation)
                             .show_ui(ui, |ui| {
@@ -2111,13 +2405,14 @@ impl LevelEditor {
         if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete))
             && let Some(comments) = self.comments.as_mut()
         {
+            self.history.mark_edited();
             comments.remove(index);
 
             if comments.is_empty() {
                 self.comments = None;
             }
 
-            self.selected_object_indices.clear();
+            self.selected_objects.clear();
             return;
         }
 
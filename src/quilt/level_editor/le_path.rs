@@ -0,0 +1,294 @@
+use super::{
+    EditMode, LevelEditor, ObjectRef,
+    le_object::{PATH_COLOR, SMALL_CIRCLE_RADIUS},
+};
+
+use crate::quilt::common::Point2D;
+
+use egui::{Pos2, Rect, Vec2};
+
+/// The length of the arrow drawn on each path segment, in pixels.
+const ARROW_LENGTH: f32 = 12.0;
+
+/// How far past the ends of the selected path the extend handles are drawn, in pixels.
+const EXTEND_DISTANCE: f32 = 24.0;
+
+/// The radius of the insert and extend handles, in pixels.
+const HANDLE_RADIUS: f32 = 4.0;
+
+pub struct PathTools {
+    /// The distance between points when a path is resampled, in world units.
+    pub resample_spacing: f32,
+}
+
+impl Default for PathTools {
+    fn default() -> Self {
+        Self {
+            resample_spacing: 2.0,
+        }
+    }
+}
+
+/// Places points `spacing` apart along the path, keeping its first and last points.
+pub fn resample(points: &[Point2D], spacing: f32) -> Vec<Point2D> {
+    // a single point has nothing to place points along
+    let [first, .., last] = points else {
+        return points.to_vec();
+    };
+
+    if spacing <= 0.0 {
+        return points.to_vec();
+    }
+
+    let mut resampled = vec![*first];
+    // how far along the path the next point goes
+    let mut next = spacing;
+    let mut travelled = 0.0;
+
+    for segment in points.windows(2) {
+        let (start, end) = (segment[0].get_vec2(), segment[1].get_vec2());
+        let length = (end - start).length();
+
+        while length > 0.0 && next < travelled + length {
+            let t = (next - travelled) / length;
+            resampled.push(Point2D::from_vec2(start + (end - start) * t));
+            next += spacing;
+        }
+
+        travelled += length;
+    }
+
+    // a point too close to the end would make a tiny last segment
+    if resampled.len() > 1
+        && let Some(point) = resampled.last()
+        && (point.get_vec2() - last.get_vec2()).length() < spacing / 2.0
+    {
+        resampled.pop();
+    }
+
+    resampled.push(*last);
+    resampled
+}
+
+/// Moves each point halfway towards the average of its neighbours, keeping the ends in place.
+pub fn smooth(points: &[Point2D]) -> Vec<Point2D> {
+    let mut smoothed = points.to_vec();
+
+    for i in 1..points.len().saturating_sub(1) {
+        let (previous, current, next) = (
+            points[i - 1].get_vec2(),
+            points[i].get_vec2(),
+            points[i + 1].get_vec2(),
+        );
+
+        let average = (previous + next) / 2.0;
+        smoothed[i] = Point2D::from_vec2(current + (average - current) / 2.0);
+    }
+
+    smoothed
+}
+
+impl LevelEditor {
+    /// Draws the direction of every path, and the point handles of the selected path.
+    /// Clicking a handle on a segment inserts a point there, and clicking a handle past
+    /// either end extends the path. Alt-clicking a point deletes it.
+    pub(super) fn process_path_overlay(&mut self, ui: &egui::Ui, rect: Rect) {
+        let painter = ui.painter_at(rect);
+        let to_screen = |point: Point2D| rect.min + self.camera.convert_to_camera(point.get_vec2());
+
+        for path in self.current_mapdata.paths.iter() {
+            if path.name == "NONE" && !self.display_none {
+                continue;
+            }
+
+            for segment in path.points.windows(2) {
+                let (start, end) = (to_screen(segment[0]), to_screen(segment[1]));
+                let direction = (end - start).normalized();
+
+                // segments too short to fit an arrow are skipped
+                if (end - start).length() < ARROW_LENGTH * 2.0 {
+                    continue;
+                }
+
+                let mid = start + (end - start) / 2.0;
+                painter.arrow(
+                    mid - direction * ARROW_LENGTH / 2.0,
+                    direction * ARROW_LENGTH,
+                    egui::Stroke::new(1.0_f32, PATH_COLOR),
+                );
+            }
+        }
+
+        if !matches!(self.path_edit_mode, EditMode::Edit) {
+            return;
+        }
+
+        let [object @ ObjectRef::Path(_)] = self.selected_objects.as_slice() else {
+            return;
+        };
+
+        let Some(index) = self.object_index(*object) else {
+            return;
+        };
+
+        let points = self.current_mapdata.paths[index].points.clone();
+        let ctx = ui.ctx();
+
+        let font = egui::FontId::monospace(11.0);
+
+        for (i, point) in points.iter().enumerate() {
+            painter.text(
+                to_screen(*point) + Vec2::new(6.0, -6.0),
+                egui::Align2::LEFT_BOTTOM,
+                (i + 1).to_string(),
+                font.clone(),
+                egui::Color32::WHITE,
+            );
+        }
+
+        // handles for inserting a point in the middle of each segment
+        let mut handles: Vec<(usize, Pos2, Vec2)> = points
+            .windows(2)
+            .enumerate()
+            .map(|(i, segment)| {
+                let mid = (segment[0].get_vec2() + segment[1].get_vec2()) / 2.0;
+                (i + 1, to_screen(Point2D::from_vec2(mid)), mid)
+            })
+            .collect();
+
+        // handles for extending the path from either end
+        if let [first, second, ..] = points.as_slice() {
+            let (first_pos, second_pos) = (to_screen(*first), to_screen(*second));
+            let pos = first_pos + (first_pos - second_pos).normalized() * EXTEND_DISTANCE;
+            handles.push((0, pos, self.camera.convert_from_camera(pos - rect.min)));
+        }
+
+        if let [.., second_last, last] = points.as_slice() {
+            let (last_pos, second_last_pos) = (to_screen(*last), to_screen(*second_last));
+            let pos = last_pos + (last_pos - second_last_pos).normalized() * EXTEND_DISTANCE;
+            handles.push((
+                points.len(),
+                pos,
+                self.camera.convert_from_camera(pos - rect.min),
+            ));
+        }
+
+        let mut insert = None;
+
+        for (i, (insert_index, pos, world_pos)) in handles.into_iter().enumerate() {
+            let resp = ui.interact(
+                Rect::from_center_size(pos, Vec2::splat(HANDLE_RADIUS * 2.0)),
+                egui::Id::new(("le_path_handle", i)),
+                egui::Sense::click(),
+            );
+
+            let stroke_width = if resp.hovered() { 2.0 } else { 1.0 };
+            painter.circle_stroke(
+                pos,
+                HANDLE_RADIUS,
+                egui::Stroke::new(stroke_width, PATH_COLOR),
+            );
+
+            if resp.clicked() {
+                insert = Some((insert_index, world_pos));
+            }
+        }
+
+        let path = &mut self.current_mapdata.paths[index];
+
+        if let Some((insert_index, world_pos)) = insert {
+            self.history.label_next("Insert Path Point");
+            let point = Point2D::from_vec2(self.snap.snap_point(world_pos));
+            path.points.insert(insert_index, point);
+            return;
+        }
+
+        // a path needs at least two points
+        if ctx.input(|i| i.modifiers.alt && i.pointer.primary_clicked())
+            && path.points.len() > 2
+            && let Some(pointer) = ctx.pointer_interact_pos()
+        {
+            let radius = (SMALL_CIRCLE_RADIUS * self.camera.zoom).max(HANDLE_RADIUS);
+
+            if let Some(i) = points
+                .iter()
+                .position(|point| (to_screen(*point) - pointer).length() <= radius)
+            {
+                self.history.label_next("Delete Path Point");
+                path.points.remove(i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(f32, f32)]) -> Vec<Point2D> {
+        coords.iter().map(|&(x, y)| Point2D { x, y }).collect()
+    }
+
+    #[test]
+    fn resample_keeps_short_paths() {
+        assert_eq!(resample(&[], 1.0), Vec::new());
+        assert_eq!(resample(&points(&[(1.0, 2.0)]), 1.0), points(&[(1.0, 2.0)]));
+    }
+
+    #[test]
+    fn resample_places_points_evenly() {
+        let path = points(&[(0.0, 0.0), (4.0, 0.0)]);
+
+        assert_eq!(
+            resample(&path, 1.0),
+            points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (4.0, 0.0)])
+        );
+    }
+
+    #[test]
+    fn resample_follows_corners() {
+        let path = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)]);
+
+        assert_eq!(
+            resample(&path, 1.0),
+            points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (2.0, 2.0)])
+        );
+    }
+
+    #[test]
+    fn resample_drops_points_close_to_the_end() {
+        let path = points(&[(0.0, 0.0), (2.2, 0.0)]);
+
+        assert_eq!(
+            resample(&path, 1.0),
+            points(&[(0.0, 0.0), (1.0, 0.0), (2.2, 0.0)])
+        );
+    }
+
+    #[test]
+    fn resample_ignores_invalid_spacing() {
+        let path = points(&[(0.0, 0.0), (4.0, 0.0)]);
+        assert_eq!(resample(&path, 0.0), path);
+    }
+
+    #[test]
+    fn smooth_keeps_the_ends() {
+        let path = points(&[(0.0, 0.0), (1.0, 2.0), (2.0, 0.0)]);
+        let smoothed = smooth(&path);
+
+        assert_eq!(smoothed.first(), path.first());
+        assert_eq!(smoothed.last(), path.last());
+    }
+
+    #[test]
+    fn smooth_moves_points_halfway_to_their_neighbours() {
+        let path = points(&[(0.0, 0.0), (1.0, 2.0), (2.0, 0.0)]);
+        assert_eq!(smooth(&path)[1], Point2D { x: 1.0, y: 1.0 });
+    }
+
+    #[test]
+    fn smooth_keeps_straight_paths() {
+        let path = points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)]);
+        assert_eq!(smooth(&path), path);
+    }
+}
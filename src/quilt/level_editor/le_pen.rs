@@ -0,0 +1,343 @@
+use super::{COLLISION_TYPES, LevelEditor, ObjectRef};
+
+use crate::quilt::{
+    common::Point2D,
+    game::mapdata::{LabeledWall, Wall},
+};
+
+use egui::{Pos2, Rect, Vec2};
+
+const PEN_COLOR: egui::Color32 = egui::Color32::from_rgb(0x00, 0x9F, 0xFD);
+
+/// The length of the normal tick drawn on each segment, in pixels.
+const NORMAL_LENGTH: f32 = 10.0;
+
+/// Draws a chain of connected walls, one click per point.
+pub struct WallPen {
+    /// Whether the pen is being used.
+    pub active: bool,
+    /// Whether `LabeledWall`s are drawn instead of `Wall`s.
+    labeled: bool,
+    /// The points placed so far, in world units.
+    points: Vec<Vec2>,
+    collision_type: String,
+    label: String,
+    /// Whether the last point is connected back to the first when finishing.
+    close_loop: bool,
+}
+
+impl Default for WallPen {
+    fn default() -> Self {
+        Self {
+            active: false,
+            labeled: false,
+            points: Vec::new(),
+            collision_type: String::from("NML"),
+            label: String::new(),
+            close_loop: false,
+        }
+    }
+}
+
+/// The normal of the segment from `start` to `end`, in world units. See `Wall::get_normalized_vector`.
+fn segment_normal(start: Vec2, end: Vec2) -> Vec2 {
+    let direction = (end - start).normalized();
+    Vec2::new(-direction.y, direction.x)
+}
+
+impl LevelEditor {
+    pub(super) fn start_wall_pen(&mut self, labeled: bool) {
+        // the pen uses the collision type of the selected wall, if there is one
+        let selected_type = match self.selected_objects.as_slice() {
+            [object @ ObjectRef::Wall(_)] => self
+                .object_index(*object)
+                .map(|i| self.current_mapdata.walls[i].collision_type.clone()),
+            [object @ ObjectRef::LabeledWall(_)] => self
+                .object_index(*object)
+                .map(|i| self.current_mapdata.labeled_walls[i].collision_type.clone()),
+            _ => None,
+        };
+
+        if let Some(collision_type) = selected_type {
+            self.wall_pen.collision_type = collision_type;
+        }
+
+        self.deselect_all();
+        self.current_add_object = None;
+        self.collision_brush.active = false;
+
+        self.wall_pen.active = true;
+        self.wall_pen.labeled = labeled;
+        self.wall_pen.points.clear();
+    }
+
+    /// Turns the points placed so far into walls, and selects them.
+    fn finish_wall_pen(&mut self) {
+        let pen = std::mem::take(&mut self.wall_pen.points);
+        self.wall_pen.active = false;
+
+        let mut segments: Vec<(Vec2, Vec2)> = pen.windows(2).map(|p| (p[0], p[1])).collect();
+
+        if self.wall_pen.close_loop
+            && pen.len() > 2
+            && let (Some(first), Some(last)) = (pen.first(), pen.last())
+            && first != last
+        {
+            segments.push((*last, *first));
+        }
+
+        if segments.is_empty() {
+            return;
+        }
+
+        self.history.label_next(if self.wall_pen.labeled {
+            "Draw Labeled Walls"
+        } else {
+            "Draw Walls"
+        });
+
+        let mapdata = &mut self.current_mapdata;
+        let collision_type = self.wall_pen.collision_type.clone();
+
+        if !mapdata.colbin_types.names.contains(&collision_type) {
+            mapdata.colbin_types.names.push(collision_type.clone());
+        }
+
+        let mut added = Vec::new();
+
+        for (start, end) in segments {
+            let start = Point2D::from_vec2(start);
+            let end = Point2D::from_vec2(end);
+
+            if self.wall_pen.labeled {
+                let wall = LabeledWall {
+                    collision_type: collision_type.clone(),
+                    label: self.wall_pen.label.clone(),
+                    start,
+                    end,
+                    ..Default::default()
+                };
+
+                added.push(ObjectRef::LabeledWall(wall.id));
+                mapdata.labeled_walls.push(wall);
+            } else {
+                let wall = Wall {
+                    collision_type: collision_type.clone(),
+                    start,
+                    end,
+                    ..Default::default()
+                };
+
+                added.push(ObjectRef::Wall(wall.id));
+                mapdata.walls.push(wall);
+            }
+        }
+
+        if self.wall_pen.labeled && !mapdata.wall_labels.names.contains(&self.wall_pen.label) {
+            mapdata.wall_labels.names.push(self.wall_pen.label.clone());
+        }
+
+        for object in added {
+            self.select_object(object);
+        }
+    }
+
+    /// Where the next point would go if the mouse was clicked at `pointer`, in world units.
+    fn next_pen_point(&self, ctx: &egui::Context, pointer: Vec2) -> Vec2 {
+        // the first point can be snapped to, to close the loop
+        if let Some(first) = self.wall_pen.points.first()
+            && self
+                .snap
+                .is_in_snap_radius(pointer, *first, self.camera.zoom)
+        {
+            return *first;
+        }
+
+        let vertices = self.wall_vertices();
+
+        match self.wall_pen.points.last() {
+            Some(last) => self.snap.snap_endpoint(
+                pointer,
+                *last,
+                ctx.input(|i| i.modifiers.shift),
+                &vertices,
+                None,
+                self.camera.zoom,
+            ),
+
+            None => self
+                .snap
+                .snap_to_vertex(pointer, &vertices, None, self.camera.zoom)
+                .unwrap_or_else(|| self.snap.snap_point(pointer)),
+        }
+    }
+
+    /// Places points where the canvas is clicked, and draws the walls so far.
+    /// Clicking the first point again, or pressing Enter, finishes the chain.
+    pub(super) fn process_wall_pen(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        rect: Rect,
+    ) {
+        let ctx = ui.ctx();
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.wall_pen.active = false;
+            self.wall_pen.points.clear();
+            return;
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) && !ctx.wants_keyboard_input() {
+            self.finish_wall_pen();
+            return;
+        }
+
+        self.show_wall_pen_ui(ctx);
+
+        if !self.wall_pen.active {
+            return;
+        }
+
+        let to_screen = |point: Vec2| rect.min + self.camera.convert_to_camera(point);
+
+        // clicks go to the canvas, even over existing walls, so that chains can connect to them
+        let pointer = ctx
+            .pointer_hover_pos()
+            .filter(|pos| rect.contains(*pos) && ctx.layer_id_at(*pos) == Some(ui.layer_id()));
+
+        let next = pointer.map(|pos| {
+            let world = self.camera.convert_from_camera(pos - rect.min);
+            self.next_pen_point(ctx, world)
+        });
+
+        let painter = ui.painter_at(rect);
+        let mut segments: Vec<(Vec2, Vec2)> = self
+            .wall_pen
+            .points
+            .windows(2)
+            .map(|p| (p[0], p[1]))
+            .collect();
+
+        if let (Some(last), Some(next)) = (self.wall_pen.points.last(), next) {
+            segments.push((*last, next));
+        }
+
+        if self.wall_pen.close_loop
+            && self.wall_pen.points.len() > 1
+            && let (Some(first), Some(last)) = (self.wall_pen.points.first(), segments.last())
+        {
+            segments.push((last.1, *first));
+        }
+
+        for (start, end) in segments {
+            if start == end {
+                continue;
+            }
+
+            let (start_pos, end_pos) = (to_screen(start), to_screen(end));
+            painter.line_segment([start_pos, end_pos], egui::Stroke::new(1.0_f32, PEN_COLOR));
+
+            // the normal is flipped, as the y axis points down on the screen
+            let normal = segment_normal(start, end);
+            let mid = Pos2::new(
+                (start_pos.x + end_pos.x) / 2.0,
+                (start_pos.y + end_pos.y) / 2.0,
+            );
+
+            painter.line_segment(
+                [mid, mid + Vec2::new(normal.x, -normal.y) * NORMAL_LENGTH],
+                egui::Stroke::new(1.0_f32, PEN_COLOR),
+            );
+        }
+
+        for point in self.wall_pen.points.iter() {
+            painter.circle_filled(to_screen(*point), 3.0, PEN_COLOR);
+        }
+
+        if let Some(next) = next {
+            painter.circle_stroke(to_screen(next), 3.0, egui::Stroke::new(1.0_f32, PEN_COLOR));
+        }
+
+        if let Some(next) = next
+            && ctx.input(|i| i.pointer.primary_clicked())
+            && !response.dragged()
+        {
+            let points = &mut self.wall_pen.points;
+
+            if points.len() > 2 && points.first() == Some(&next) {
+                // clicking the first point closes the loop
+                points.push(next);
+                self.finish_wall_pen();
+            } else if points.last() != Some(&next) {
+                points.push(next);
+            }
+        }
+    }
+
+    fn show_wall_pen_ui(&mut self, ctx: &egui::Context) {
+        let mut finish = false;
+        let mut cancel = false;
+        let pen = &mut self.wall_pen;
+
+        egui::Area::new(egui::Id::from("le_wall_pen"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 10.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .inner_margin(egui::Vec2::splat(8.0))
+                    .show(ui, |ui| {
+                        ui.label(if pen.labeled {
+                            "Draw labeled walls"
+                        } else {
+                            "Draw walls"
+                        });
+
+                        ui.label("Click to place points. Hold Shift to lock angles.");
+
+                        egui::ComboBox::from_label("Collision Type")
+                            .selected_text(&pen.collision_type)
+                            .show_ui(ui, |ui| {
+                                for collision_type in COLLISION_TYPES {
+                                    ui.selectable_value(
+                                        &mut pen.collision_type,
+                                        collision_type.to_string(),
+                                        collision_type,
+                                    );
+                                }
+                            });
+
+                        ui.add(
+                            egui::TextEdit::singleline(&mut pen.collision_type).char_limit(0x20),
+                        );
+
+                        if pen.labeled {
+                            ui.label("Label");
+                            ui.add(egui::TextEdit::singleline(&mut pen.label).char_limit(0x20));
+                        }
+
+                        ui.checkbox(&mut pen.close_loop, "Close loop");
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Finish")
+                                .on_hover_text("Enter, or click the first point")
+                                .clicked()
+                            {
+                                finish = true;
+                            }
+
+                            if ui.button("Cancel").on_hover_text("Escape").clicked() {
+                                cancel = true;
+                            }
+                        });
+                    });
+            });
+
+        if finish {
+            self.finish_wall_pen();
+        } else if cancel {
+            self.wall_pen.active = false;
+            self.wall_pen.points.clear();
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use super::{
+    LevelEditor, ObjectType,
+    le_clipboard::ObjectGroup,
+    le_object::{
+        COMMENT_COLOR, COMMON_GIMMICK_COLOR, COURSE_INFO_COLOR, ENEMY_COLOR, GIMMICK_COLOR,
+        PATH_COLOR, SQUARE_SIZE, ZONE_COLOR,
+    },
+};
+
+use crate::quilt::common::{Point2D, quilt_res_path};
+
+use anyhow::{Result, bail};
+use egui::{Pos2, Rect, Vec2};
+use std::{fs, path::PathBuf};
+
+const PREFAB_FOLDER_NAME: &str = "prefabs";
+const THUMBNAIL_SIZE: f32 = 96.0;
+
+/// A saved group of objects, placed relative to the center of its bounding box.
+struct Prefab {
+    name: String,
+    objects: ObjectGroup,
+}
+
+#[derive(Default)]
+pub struct PrefabLibrary {
+    prefabs: Vec<Prefab>,
+    loaded: bool,
+    pub show_panel: bool,
+    new_name: String,
+    /// A prefab named `new_name` already exists, and the user is being asked to replace it.
+    confirm_replace: bool,
+    /// Why the last prefab couldn't be saved.
+    save_error: Option<String>,
+}
+
+impl PrefabLibrary {
+    fn folder_path() -> Result<PathBuf> {
+        Ok(quilt_res_path()?.join(PREFAB_FOLDER_NAME))
+    }
+
+    /// Reads every prefab in the prefab folder.
+    fn load(&mut self) -> Result<()> {
+        self.loaded = true;
+        self.prefabs.clear();
+
+        let folder = Self::folder_path()?;
+
+        if !fs::exists(&folder)? {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(folder)?.flatten() {
+            let path = entry.path();
+
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+
+            let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+
+            // files that aren't prefabs are skipped
+            if let Ok(contents) = fs::read_to_string(&path)
+                && let Some(objects) = ObjectGroup::from_json(&contents)
+            {
+                self.prefabs.push(Prefab { name, objects });
+            }
+        }
+
+        self.prefabs.sort_by_key(|p| p.name.to_lowercase());
+
+        Ok(())
+    }
+
+    fn save(&mut self, name: &str, mut objects: ObjectGroup) -> Result<()> {
+        if name.is_empty() || name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']) {
+            bail!("invalid prefab name");
+        }
+
+        // positions are stored relative to the center
+        if let Some(center) = objects.center() {
+            objects.translate(-center);
+        }
+
+        let folder = Self::folder_path()?;
+
+        if !fs::exists(&folder)? {
+            fs::create_dir_all(&folder)?;
+        }
+
+        let contents = serde_json::to_string_pretty(&objects)?;
+        fs::write(folder.join(format!("{name}.json")), contents)?;
+
+        self.load()
+    }
+
+    fn delete(&mut self, name: &str) -> Result<()> {
+        fs::remove_file(Self::folder_path()?.join(format!("{name}.json")))?;
+        self.load()
+    }
+
+    fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.iter().find(|p| p.name == name)
+    }
+}
+
+/// Draws a simplified version of the objects: lines for walls and paths,
+/// outlines for zones and squares for everything else.
+fn draw_object_group(
+    painter: &egui::Painter,
+    objects: &ObjectGroup,
+    to_screen: impl Fn(Point2D) -> Pos2,
+    square_size: f32,
+) {
+    let line = |start: Point2D, end: Point2D, color| {
+        painter.line_segment(
+            [to_screen(start), to_screen(end)],
+            egui::Stroke::new(1.0_f32, color),
+        );
+    };
+
+    let square = |pos: Point2D, color| {
+        painter.rect_stroke(
+            Rect::from_center_size(to_screen(pos), Vec2::splat(square_size)),
+            0.0,
+            egui::Stroke::new(1.0_f32, color),
+        );
+    };
+
+    for wall in objects.walls.iter() {
+        line(wall.start, wall.end, egui::Color32::WHITE);
+    }
+
+    for wall in objects.labeled_walls.iter() {
+        line(wall.start, wall.end, egui::Color32::WHITE);
+    }
+
+    for path in objects.paths.iter() {
+        for points in path.points.windows(2) {
+            line(points[0], points[1], PATH_COLOR);
+        }
+    }
+
+    for zone in objects.zones.iter() {
+        painter.rect_stroke(
+            Rect::from_two_pos(to_screen(zone.bounds_start), to_screen(zone.bounds_end)),
+            0.0,
+            egui::Stroke::new(1.0_f32, ZONE_COLOR),
+        );
+    }
+
+    for gmk in objects.common_gimmicks.iter() {
+        square(gmk.position.get_point2d(), COMMON_GIMMICK_COLOR);
+    }
+
+    for gmk in objects.gimmicks.iter() {
+        square(gmk.position.get_point2d(), GIMMICK_COLOR);
+    }
+
+    for info in objects.course_infos.iter() {
+        square(info.position.get_point2d(), COURSE_INFO_COLOR);
+    }
+
+    for enemy in objects.enemies.iter() {
+        square(enemy.position_1.get_point2d(), ENEMY_COLOR);
+    }
+
+    for comment in objects.comments.iter() {
+        painter.circle_stroke(
+            to_screen(comment.position),
+            square_size / 2.0,
+            egui::Stroke::new(1.0_f32, COMMENT_COLOR),
+        );
+    }
+}
+
+/// Draws the objects scaled to fit inside `rect`.
+fn draw_thumbnail(painter: &egui::Painter, objects: &ObjectGroup, rect: Rect) {
+    let points = objects.points();
+
+    let Some(first) = points.first() else {
+        return;
+    };
+
+    let (min, max) = points
+        .iter()
+        .fold((first.get_vec2(), first.get_vec2()), |(min, max), point| {
+            (min.min(point.get_vec2()), max.max(point.get_vec2()))
+        });
+
+    let inner = rect.shrink(8.0);
+    let size = (max - min).max(Vec2::splat(1.0));
+    let scale = (inner.width() / size.x).min(inner.height() / size.y);
+    let center = (min + max) / 2.0;
+
+    // the y axis points up in the level, but down on the screen
+    let to_screen =
+        |point: Point2D| inner.center() + Vec2::new(point.x - center.x, center.y - point.y) * scale;
+
+    draw_object_group(painter, objects, to_screen, 4.0);
+}
+
+impl LevelEditor {
+    /// Saves the selection as a prefab named `new_name`, which is cleared once it's saved.
+    fn save_prefab(&mut self) {
+        let name = self.prefabs.new_name.clone();
+        let objects = self.copy_selection();
+
+        match self.prefabs.save(&name, objects) {
+            Ok(()) => {
+                self.prefabs.new_name.clear();
+                self.prefabs.save_error = None;
+            }
+
+            Err(e) => {
+                eprintln!("Failed to save prefab. Reason: {:?}", e);
+                self.prefabs.save_error = Some(format!("Failed to save prefab: {e}"));
+            }
+        }
+    }
+
+    pub fn show_prefab_ui(&mut self, ui: &mut egui::Ui) {
+        if !self.prefabs.loaded
+            && let Err(e) = self.prefabs.load()
+        {
+            eprintln!("Failed to load prefabs. Reason: {:?}", e);
+        }
+
+        ui.label("Save selection as prefab");
+        ui.horizontal(|ui| {
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.prefabs.new_name)
+                        .hint_text("Name")
+                        .desired_width(150.0),
+                )
+                .changed()
+            {
+                self.prefabs.confirm_replace = false;
+                self.prefabs.save_error = None;
+            }
+
+            if ui
+                .add_enabled(
+                    !self.selected_objects.is_empty() && !self.prefabs.new_name.is_empty(),
+                    egui::Button::new("Save"),
+                )
+                .on_hover_text("Parameters are saved along with the objects.")
+                .clicked()
+            {
+                if self.prefabs.get(&self.prefabs.new_name).is_some() {
+                    self.prefabs.confirm_replace = true;
+                } else {
+                    self.save_prefab();
+                }
+            }
+        });
+
+        if self.prefabs.confirm_replace {
+            ui.label(format!(
+                "A prefab named '{}' already exists. Replace it?",
+                self.prefabs.new_name
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button("Replace").clicked() {
+                    self.prefabs.confirm_replace = false;
+                    self.save_prefab();
+                }
+
+                if ui.button("Cancel").clicked() {
+                    self.prefabs.confirm_replace = false;
+                }
+            });
+        }
+
+        if let Some(error) = self.prefabs.save_error.as_ref() {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        if ui
+            .button("Refresh")
+            .on_hover_text("Reloads the prefabs from 'quilt_res/prefabs'.")
+            .clicked()
+            && let Err(e) = self.prefabs.load()
+        {
+            eprintln!("Failed to load prefabs. Reason: {:?}", e);
+        }
+
+        ui.separator();
+
+        if self.prefabs.prefabs.is_empty() {
+            ui.label("No prefabs saved yet.");
+            return;
+        }
+
+        ui.label("Click a prefab, then click on the canvas to place it.");
+
+        let mut to_delete = None;
+
+        egui::ScrollArea::vertical()
+            .id_salt("le_prefabs")
+            .max_height(400.0)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for prefab in self.prefabs.prefabs.iter() {
+                        let is_placing = matches!(
+                            &self.current_add_object,
+                            Some(ObjectType::Prefab(name)) if *name == prefab.name
+                        );
+
+                        let resp = ui
+                            .vertical(|ui| {
+                                ui.set_width(THUMBNAIL_SIZE);
+
+                                let (rect, resp) = ui.allocate_exact_size(
+                                    Vec2::splat(THUMBNAIL_SIZE),
+                                    egui::Sense::click(),
+                                );
+
+                                let painter = ui.painter_at(rect);
+                                painter.rect_filled(rect, 2.0, egui::Color32::BLACK);
+
+                                if is_placing || resp.hovered() {
+                                    painter.rect_stroke(
+                                        rect,
+                                        2.0,
+                                        egui::Stroke::new(1.0_f32, egui::Color32::LIGHT_BLUE),
+                                    );
+                                }
+
+                                draw_thumbnail(&painter, &prefab.objects, rect);
+
+                                ui.add(egui::Label::new(&prefab.name).truncate());
+
+                                resp
+                            })
+                            .inner;
+
+                        if resp.clicked() {
+                            self.current_add_object = Some(ObjectType::Prefab(prefab.name.clone()));
+                        }
+
+                        resp.context_menu(|ui| {
+                            if ui.button("Delete").clicked() {
+                                to_delete = Some(prefab.name.clone());
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                });
+            });
+
+        if let Some(name) = to_delete
+            && let Err(e) = self.prefabs.delete(&name)
+        {
+            eprintln!("Failed to delete prefab. Reason: {:?}", e);
+        }
+    }
+
+    /// Draws the prefab being placed under the mouse, and places it when the canvas is clicked.
+    pub(super) fn process_prefab_placement(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        rect: Rect,
+        name: &str,
+    ) {
+        let Some(prefab) = self.prefabs.get(name) else {
+            self.current_add_object = None;
+            return;
+        };
+
+        let Some(pointer_pos) = response.hover_pos() else {
+            return;
+        };
+
+        let target = self
+            .snap
+            .snap_point(self.camera.convert_from_camera(pointer_pos - rect.min));
+        let to_screen =
+            |point: Point2D| rect.min + self.camera.convert_to_camera(point.get_vec2() + target);
+
+        draw_object_group(
+            &ui.painter_at(rect),
+            &prefab.objects,
+            to_screen,
+            SQUARE_SIZE * self.camera.zoom,
+        );
+
+        if response.hovered() && ui.ctx().input(|i| i.pointer.any_released()) {
+            let mut objects = prefab.objects.clone();
+            objects.translate(target);
+
+            self.history.label_next("Place Prefab");
+            self.paste_objects(ui.ctx(), objects);
+            self.current_add_object = None;
+        }
+    }
+}
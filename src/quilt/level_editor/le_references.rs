@@ -0,0 +1,414 @@
+//! Objects can name other objects in their string parameters.
+//! A parameter in `objectdata.json` is a reference if it has a `reference` key, which is either
+//! the category of the objects it names (`paths`, `zones`, `gimmicks` or `course_infos`),
+//! matched by name, or `link`, which matches any object with the same value in a parameter
+//! marked `"link_id": true`.
+
+use super::{LevelEditor, ObjectRef};
+
+use egui::{Pos2, Rect, Vec2};
+use std::collections::HashMap;
+
+const REFERENCE_COLOR: egui::Color32 = egui::Color32::from_rgb(0xB8, 0x8A, 0xFF);
+const DANGLING_COLOR: egui::Color32 = egui::Color32::from_rgb(0xFF, 0x40, 0x40);
+
+/// The categories that references can name objects from.
+const NAMED_CATEGORIES: [&str; 4] = ["paths", "zones", "gimmicks", "course_infos"];
+
+/// One parameter of one object that names another object.
+pub(super) struct Reference {
+    pub from: ObjectRef,
+    /// The object named, or `None` if there isn't one.
+    pub to: Option<ObjectRef>,
+    pub param: String,
+    pub value: String,
+}
+
+/// An object that can refer to, or be referred to by, other objects.
+struct Node<'a> {
+    object: ObjectRef,
+    /// The object's category in `objectdata.json`.
+    category: &'static str,
+    /// The object's key in its category.
+    key: &'a str,
+    string_params: &'a [String],
+    /// The common string of a common gimmick.
+    common_string_param: Option<&'a str>,
+    position: Vec2,
+}
+
+/// The string parameters of an object with `mark` set in `objectdata.json`,
+/// with their names, values and the value of `mark`.
+fn marked_params<'a, 'j>(
+    json: &'j serde_json::Value,
+    node: &Node<'a>,
+    mark: &str,
+) -> Vec<(&'j str, &'a str, &'j serde_json::Value)> {
+    let Some(object) = json.get(node.category).and_then(|c| c.get(node.key)) else {
+        return Vec::new();
+    };
+
+    let mut params = Vec::new();
+
+    for group in ["parameters", "common_parameters"] {
+        let Some(group_params) = object.get(group).and_then(|p| p.as_object()) else {
+            continue;
+        };
+
+        for (name, data) in group_params {
+            let Some(marked) = data.get(mark) else {
+                continue;
+            };
+
+            // common gimmicks only have one common string
+            let value = if group == "common_parameters" {
+                node.common_string_param
+            } else {
+                data.get("slot")
+                    .and_then(|s| s.as_u64())
+                    .and_then(|slot| node.string_params.get(slot as usize))
+                    .map(|v| v.as_str())
+            };
+
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                params.push((name.as_str(), value, marked));
+            }
+        }
+    }
+
+    params
+}
+
+impl LevelEditor {
+    fn reference_nodes(&self) -> Vec<Node<'_>> {
+        let mapdata = &self.current_mapdata;
+        let mut nodes = Vec::new();
+
+        for gmk in mapdata.common_gimmicks.iter() {
+            nodes.push(Node {
+                object: ObjectRef::CommonGimmick(gmk.id),
+                category: "common_gimmicks",
+                key: &gmk.hex,
+                string_params: &gmk.params.string_params,
+                common_string_param: Some(&gmk.params.common_string_param),
+                position: gmk.position.get_point2d().get_vec2(),
+            });
+        }
+
+        for gmk in mapdata.gimmicks.iter() {
+            nodes.push(Node {
+                object: ObjectRef::Gimmick(gmk.id),
+                category: "gimmicks",
+                key: &gmk.name,
+                string_params: &gmk.params.string_params,
+                common_string_param: None,
+                position: gmk.position.get_point2d().get_vec2(),
+            });
+        }
+
+        for path in mapdata.paths.iter() {
+            nodes.push(Node {
+                object: ObjectRef::Path(path.id),
+                category: "paths",
+                key: &path.name,
+                string_params: &path.params.string_params,
+                common_string_param: None,
+                position: path
+                    .points
+                    .first()
+                    .map(|p| p.get_vec2())
+                    .unwrap_or_default(),
+            });
+        }
+
+        for zone in mapdata.zones.iter() {
+            nodes.push(Node {
+                object: ObjectRef::Zone(zone.id),
+                category: "zones",
+                key: &zone.name,
+                string_params: &zone.params.string_params,
+                common_string_param: None,
+                position: (zone.bounds_start.get_vec2() + zone.bounds_end.get_vec2()) / 2.0,
+            });
+        }
+
+        for info in mapdata.course_infos.iter() {
+            nodes.push(Node {
+                object: ObjectRef::CourseInfo(info.id),
+                category: "course_infos",
+                key: &info.name,
+                string_params: &info.params.string_params,
+                common_string_param: None,
+                position: info.position.get_point2d().get_vec2(),
+            });
+        }
+
+        nodes
+    }
+
+    /// Every reference in the level, including the paths used by enemies.
+    pub(super) fn references(&self) -> Vec<Reference> {
+        let nodes = self.reference_nodes();
+        let mut references = Vec::new();
+
+        // objects by category and name, and by the values other objects can link to
+        let mut named: HashMap<(&str, &str), Vec<ObjectRef>> = HashMap::new();
+        let mut linked: HashMap<&str, Vec<ObjectRef>> = HashMap::new();
+
+        for node in nodes.iter() {
+            named
+                .entry((node.category, node.key))
+                .or_default()
+                .push(node.object);
+        }
+
+        let mut push = |from: ObjectRef, targets: &[ObjectRef], param: &str, value: &str| {
+            let targets: Vec<ObjectRef> =
+                targets.iter().copied().filter(|to| *to != from).collect();
+
+            if targets.is_empty() {
+                references.push(Reference {
+                    from,
+                    to: None,
+                    param: param.to_string(),
+                    value: value.to_string(),
+                });
+            }
+
+            for to in targets {
+                references.push(Reference {
+                    from,
+                    to: Some(to),
+                    param: param.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        };
+
+        if let Some(json) = self.object_data_json.as_ref() {
+            for node in nodes.iter() {
+                for (_, value, link_id) in marked_params(json, node, "link_id") {
+                    if link_id.as_bool() == Some(true) {
+                        linked.entry(value).or_default().push(node.object);
+                    }
+                }
+            }
+
+            for node in nodes.iter() {
+                for (param, value, target) in marked_params(json, node, "reference") {
+                    let targets = match target.as_str() {
+                        Some("link") => linked.get(value),
+                        Some(category) if NAMED_CATEGORIES.contains(&category) => {
+                            named.get(&(category, value))
+                        }
+                        _ => continue,
+                    };
+
+                    push(
+                        node.object,
+                        targets.map(|t| t.as_slice()).unwrap_or_default(),
+                        param,
+                        value,
+                    );
+                }
+            }
+        }
+
+        for enemy in self.current_endata.enemies.iter() {
+            if enemy.path_name.is_empty() {
+                continue;
+            }
+
+            let targets = named.get(&("paths", enemy.path_name.as_str()));
+
+            push(
+                ObjectRef::Enemy(enemy.id),
+                targets.map(|t| t.as_slice()).unwrap_or_default(),
+                "Path name",
+                &enemy.path_name,
+            );
+        }
+
+        references
+    }
+
+    /// Where references to and from each object are drawn, in world units.
+    fn reference_positions(&self) -> HashMap<ObjectRef, Vec2> {
+        let enemies = self.current_endata.enemies.iter().map(|enemy| {
+            (
+                ObjectRef::Enemy(enemy.id),
+                enemy.position_1.get_point2d().get_vec2(),
+            )
+        });
+
+        self.reference_nodes()
+            .into_iter()
+            .map(|n| (n.object, n.position))
+            .chain(enemies)
+            .collect()
+    }
+
+    /// A short description of an object, for listing references.
+    fn reference_label(&self, object: ObjectRef) -> String {
+        let Some(index) = self.object_index(object) else {
+            return String::from("?");
+        };
+
+        let mapdata = &self.current_mapdata;
+
+        match object {
+            ObjectRef::CommonGimmick(_) => {
+                format!("Common gimmick {}", mapdata.common_gimmicks[index].hex)
+            }
+            ObjectRef::Gimmick(_) => format!("Gimmick {}", mapdata.gimmicks[index].name),
+            ObjectRef::Path(_) => format!("Path {}", mapdata.paths[index].name),
+            ObjectRef::Zone(_) => format!("Zone {}", mapdata.zones[index].name),
+            ObjectRef::CourseInfo(_) => format!("Course info {}", mapdata.course_infos[index].name),
+            ObjectRef::Enemy(_) => format!("Enemy {}", self.current_endata.enemies[index].name),
+            _ => String::from("?"),
+        }
+    }
+
+    /// Draws an arrow from each object to the objects it refers to, and marks references
+    /// to objects that don't exist. Only the references of the selected objects are drawn,
+    /// unless every reference is shown.
+    pub(super) fn draw_references(&self, ui: &egui::Ui, rect: Rect) {
+        let painter = ui.painter_at(rect);
+        let positions = self.reference_positions();
+        let to_screen = |point: Vec2| rect.min + self.camera.convert_to_camera(point);
+
+        for reference in self.references() {
+            // enemies are linked to their paths by `draw_enemy_path_links`
+            if let ObjectRef::Enemy(_) = reference.from {
+                continue;
+            }
+
+            let is_selected = self.selected_objects.contains(&reference.from)
+                || reference
+                    .to
+                    .is_some_and(|to| self.selected_objects.contains(&to));
+
+            if !self.show_references && !is_selected {
+                continue;
+            }
+
+            let Some(from) = positions.get(&reference.from).copied().map(to_screen) else {
+                continue;
+            };
+
+            let Some(to) = reference
+                .to
+                .and_then(|to| positions.get(&to).copied())
+                .map(to_screen)
+            else {
+                painter.text(
+                    from + Vec2::new(-8.0, 8.0),
+                    egui::Align2::RIGHT_TOP,
+                    "!",
+                    egui::FontId::proportional(14.0),
+                    DANGLING_COLOR,
+                );
+
+                continue;
+            };
+
+            let color = if is_selected {
+                REFERENCE_COLOR
+            } else {
+                REFERENCE_COLOR.gamma_multiply(0.5)
+            };
+
+            // arrows stop short of the objects so that they don't cover them
+            let offset = to - from;
+
+            if offset.length() < 16.0 {
+                continue;
+            }
+
+            let direction = offset.normalized();
+            let (start, end): (Pos2, Pos2) = (from + direction * 6.0, to - direction * 6.0);
+
+            painter.arrow(start, end - start, egui::Stroke::new(1.5_f32, color));
+        }
+    }
+
+    /// Lists the references to and from the selected object. Clicking one moves to the other object.
+    pub(super) fn show_reference_ui(&mut self, ctx: &egui::Context) {
+        let [object] = self.selected_objects.as_slice() else {
+            return;
+        };
+
+        let object = *object;
+        let references = self.references();
+
+        let outgoing: Vec<&Reference> = references.iter().filter(|r| r.from == object).collect();
+        let incoming: Vec<&Reference> =
+            references.iter().filter(|r| r.to == Some(object)).collect();
+
+        if outgoing.is_empty() && incoming.is_empty() {
+            return;
+        }
+
+        let mut jump = None;
+
+        egui::Area::new(egui::Id::from("le_reference_panel"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::Vec2::new(-10.0, -10.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .inner_margin(egui::Vec2::splat(8.0))
+                    .show(ui, |ui| {
+                        if !outgoing.is_empty() {
+                            ui.label("Refers to");
+                        }
+
+                        for reference in outgoing.iter() {
+                            match reference.to {
+                                Some(to) => {
+                                    let text = format!(
+                                        "{}: {}",
+                                        reference.param,
+                                        self.reference_label(to)
+                                    );
+
+                                    if ui.link(text).clicked() {
+                                        jump = Some(to);
+                                    }
+                                }
+
+                                None => {
+                                    ui.colored_label(
+                                        DANGLING_COLOR,
+                                        format!(
+                                            "{}: nothing named '{}'",
+                                            reference.param, reference.value
+                                        ),
+                                    );
+                                }
+                            }
+                        }
+
+                        if !incoming.is_empty() {
+                            ui.label("Referred to by");
+                        }
+
+                        for reference in incoming.iter() {
+                            let text = format!(
+                                "{} ({})",
+                                self.reference_label(reference.from),
+                                reference.param
+                            );
+
+                            if ui.link(text).clicked() {
+                                jump = Some(reference.from);
+                            }
+                        }
+                    });
+            });
+
+        if let Some(object) = jump
+            && let Some(position) = self.reference_positions().get(&object).copied()
+        {
+            self.jump_to(position, &[object]);
+        }
+    }
+}
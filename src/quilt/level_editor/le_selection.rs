@@ -0,0 +1,510 @@
+use super::{COLLISION_TYPES, EditMode, LevelEditor, ObjectRef};
+
+use crate::quilt::{
+    common::{Point2D, Point3D},
+    game::{endata::Enemy, mapdata::*},
+    util::comment::Comment,
+};
+
+use egui::{Rect, Vec2};
+
+const SELECTION_BOX_COLOR: egui::Color32 = egui::Color32::from_rgb(0x00, 0x9F, 0xFD);
+
+/// Objects that can be moved as a whole, keeping the points that make them up in place
+/// relative to each other.
+pub(super) trait Translate {
+    /// Moves the object by `delta`, in world units.
+    fn translate(&mut self, delta: Vec2);
+}
+
+fn offset(point: &mut Point2D, delta: Vec2) {
+    point.x += delta.x;
+    point.y += delta.y;
+}
+
+fn offset_3d(point: &mut Point3D, delta: Vec2) {
+    point.x += delta.x;
+    point.y += delta.y;
+}
+
+impl Translate for Wall {
+    fn translate(&mut self, delta: Vec2) {
+        offset(&mut self.start, delta);
+        offset(&mut self.end, delta);
+    }
+}
+
+impl Translate for LabeledWall {
+    fn translate(&mut self, delta: Vec2) {
+        offset(&mut self.start, delta);
+        offset(&mut self.end, delta);
+    }
+}
+
+impl Translate for CommonGimmick {
+    fn translate(&mut self, delta: Vec2) {
+        offset_3d(&mut self.position, delta);
+    }
+}
+
+impl Translate for Gimmick {
+    fn translate(&mut self, delta: Vec2) {
+        offset_3d(&mut self.position, delta);
+    }
+}
+
+impl Translate for Path {
+    fn translate(&mut self, delta: Vec2) {
+        for point in self.points.iter_mut() {
+            offset(point, delta);
+        }
+    }
+}
+
+impl Translate for Zone {
+    fn translate(&mut self, delta: Vec2) {
+        offset(&mut self.bounds_start, delta);
+        offset(&mut self.bounds_end, delta);
+    }
+}
+
+impl Translate for CourseInfo {
+    fn translate(&mut self, delta: Vec2) {
+        offset_3d(&mut self.position, delta);
+    }
+}
+
+impl Translate for Enemy {
+    /// The secondary positions are moved too, so that the enemy's patrol moves with it.
+    fn translate(&mut self, delta: Vec2) {
+        offset_3d(&mut self.position_1, delta);
+        offset_3d(&mut self.position_2, delta);
+        offset_3d(&mut self.position_3, delta);
+    }
+}
+
+impl Translate for Comment {
+    fn translate(&mut self, delta: Vec2) {
+        offset(&mut self.position, delta);
+    }
+}
+
+impl LevelEditor {
+    /// Selects an object that was clicked.
+    /// A plain click replaces the selection, Shift adds to it and Ctrl toggles the object.
+    fn click_object(&mut self, ctx: &egui::Context, object: ObjectRef) {
+        let modifiers = ctx.input(|i| i.modifiers);
+        let is_selected = self.selected_objects.contains(&object);
+
+        if modifiers.command {
+            if is_selected {
+                self.selected_objects.retain(|o| *o != object);
+                self.set_object_selected(object, false);
+            } else {
+                self.select_object(object);
+            }
+        } else if modifiers.shift {
+            self.select_object(object);
+        } else {
+            self.deselect_all();
+            self.select_object(object);
+        }
+    }
+
+    /// Applies what happened to the objects drawn on the canvas: a click changes the selection,
+    /// and dragging a selected object moves the whole selection.
+    pub(super) fn apply_object_interaction(
+        &mut self,
+        ctx: &egui::Context,
+        clicked_object: Option<ObjectRef>,
+        selection_delta: Option<Vec2>,
+    ) {
+        // clicks place points while drawing walls
+        if let Some(object) = clicked_object
+            && !self.wall_pen.active
+        {
+            self.click_object(ctx, object);
+        }
+
+        if let Some(delta) = selection_delta {
+            self.move_selection(delta);
+        }
+    }
+
+    /// Adds an object to the selection.
+    pub(super) fn select_object(&mut self, object: ObjectRef) {
+        if !self.selected_objects.contains(&object) {
+            self.selected_objects.push(object);
+        }
+
+        self.set_object_selected(object, true);
+    }
+
+    /// Moves every selected object by `delta`, in world units.
+    pub(super) fn move_selection(&mut self, delta: Vec2) {
+        fn move_selected<T: Translate>(
+            objects: &mut [T],
+            is_selected: fn(&T) -> bool,
+            delta: Vec2,
+        ) {
+            for object in objects.iter_mut().filter(|o| is_selected(o)) {
+                object.translate(delta);
+            }
+        }
+
+        let mapdata = &mut self.current_mapdata;
+
+        move_selected(&mut mapdata.walls, |o| o.is_selected, delta);
+        move_selected(&mut mapdata.labeled_walls, |o| o.is_selected, delta);
+        move_selected(&mut mapdata.common_gimmicks, |o| o.is_selected, delta);
+        move_selected(&mut mapdata.gimmicks, |o| o.is_selected, delta);
+        move_selected(&mut mapdata.paths, |o| o.is_selected, delta);
+        move_selected(&mut mapdata.zones, |o| o.is_selected, delta);
+        move_selected(&mut mapdata.course_infos, |o| o.is_selected, delta);
+        move_selected(&mut self.current_endata.enemies, |o| o.is_selected, delta);
+
+        if let Some(comments) = self.comments.as_mut() {
+            move_selected(comments, |o| o.is_selected, delta);
+        }
+    }
+
+    /// Removes every selected object.
+    fn delete_selection(&mut self) {
+        let mapdata = &mut self.current_mapdata;
+
+        mapdata.walls.retain(|o| !o.is_selected);
+        mapdata.labeled_walls.retain(|o| !o.is_selected);
+        mapdata.common_gimmicks.retain(|o| !o.is_selected);
+        mapdata.gimmicks.retain(|o| !o.is_selected);
+        mapdata.paths.retain(|o| !o.is_selected);
+        mapdata.zones.retain(|o| !o.is_selected);
+        mapdata.course_infos.retain(|o| !o.is_selected);
+        self.current_endata.enemies.retain(|o| !o.is_selected);
+
+        if let Some(comments) = self.comments.as_mut() {
+            comments.retain(|o| !o.is_selected);
+        }
+
+        self.selected_objects.clear();
+    }
+
+    /// Finds the objects that can be edited and lie entirely within `rect`, in world units.
+    fn objects_in_rect(&self, rect: Rect) -> Vec<ObjectRef> {
+        let mapdata = &self.current_mapdata;
+        let inside = |point: &Point2D| rect.contains(point.get_vec2().to_pos2());
+        let editable = |mode: EditMode| matches!(mode, EditMode::Edit);
+        let shown = |name: &str| name != "NONE" || self.display_none;
+        let wall_editable = |t: &str| self.collision_styles.get(t).is_editable();
+
+        let mut objects = Vec::new();
+
+        if editable(self.wall_edit_mode) {
+            objects.extend(
+                mapdata
+                    .walls
+                    .iter()
+                    .filter(|o| wall_editable(&o.collision_type))
+                    .filter(|o| inside(&o.start) && inside(&o.end))
+                    .map(|o| ObjectRef::Wall(o.id)),
+            );
+        }
+
+        if editable(self.labeled_wall_edit_mode) {
+            objects.extend(
+                mapdata
+                    .labeled_walls
+                    .iter()
+                    .filter(|o| wall_editable(&o.collision_type))
+                    .filter(|o| inside(&o.start) && inside(&o.end))
+                    .map(|o| ObjectRef::LabeledWall(o.id)),
+            );
+        }
+
+        if editable(self.common_gimmick_edit_mode) {
+            objects.extend(
+                mapdata
+                    .common_gimmicks
+                    .iter()
+                    .filter(|o| shown(&o.hex) && inside(&o.position.get_point2d()))
+                    .map(|o| ObjectRef::CommonGimmick(o.id)),
+            );
+        }
+
+        if editable(self.gimmick_edit_mode) {
+            objects.extend(
+                mapdata
+                    .gimmicks
+                    .iter()
+                    .filter(|o| shown(&o.name) && inside(&o.position.get_point2d()))
+                    .map(|o| ObjectRef::Gimmick(o.id)),
+            );
+        }
+
+        if editable(self.path_edit_mode) {
+            objects.extend(
+                mapdata
+                    .paths
+                    .iter()
+                    .filter(|o| shown(&o.name) && o.points.iter().all(inside))
+                    .map(|o| ObjectRef::Path(o.id)),
+            );
+        }
+
+        if editable(self.zone_edit_mode) {
+            objects.extend(
+                mapdata
+                    .zones
+                    .iter()
+                    .filter(|o| shown(&o.name) && inside(&o.bounds_start) && inside(&o.bounds_end))
+                    .map(|o| ObjectRef::Zone(o.id)),
+            );
+        }
+
+        if editable(self.course_info_edit_mode) {
+            objects.extend(
+                mapdata
+                    .course_infos
+                    .iter()
+                    .filter(|o| shown(&o.name) && inside(&o.position.get_point2d()))
+                    .map(|o| ObjectRef::CourseInfo(o.id)),
+            );
+        }
+
+        // enemies are always editable
+        objects.extend(
+            self.current_endata
+                .enemies
+                .iter()
+                .filter(|o| inside(&o.position_1.get_point2d()))
+                .map(|o| ObjectRef::Enemy(o.id)),
+        );
+
+        if editable(self.comment_edit_mode)
+            && let Some(comments) = self.comments.as_ref()
+        {
+            let current_filename = &self.archive_contents[self.selected_file_index].0;
+
+            objects.extend(
+                comments
+                    .iter()
+                    .filter(|o| &o.file == current_filename && inside(&o.position))
+                    .map(|o| ObjectRef::Comment(o.id)),
+            );
+        }
+
+        objects
+    }
+
+    /// Handles Shift+dragging a box on the empty canvas to select everything inside it.
+    /// Holding Ctrl as well adds to the selection instead of replacing it.
+    /// ### Returns
+    /// Whether a box is being dragged, in which case the canvas shouldn't be panned.
+    pub(super) fn handle_selection_box(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+    ) -> bool {
+        let rect = response.rect;
+        let modifiers = ui.ctx().input(|i| i.modifiers);
+
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && modifiers.shift
+            && self.current_add_object.is_none()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let start = self.camera.convert_from_camera(pos - rect.min);
+            self.selection_box = Some((start, start));
+        }
+
+        let Some((start, mut end)) = self.selection_box else {
+            return false;
+        };
+
+        if response.dragged() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                end = self.camera.convert_from_camera(pos - rect.min);
+                self.selection_box = Some((start, end));
+            }
+
+            let screen_rect = Rect::from_two_pos(
+                rect.min + self.camera.convert_to_camera(start),
+                rect.min + self.camera.convert_to_camera(end),
+            );
+
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(screen_rect, 0.0, SELECTION_BOX_COLOR.gamma_multiply(0.1));
+            painter.rect_stroke(
+                screen_rect,
+                0.0,
+                egui::Stroke::new(1.0_f32, SELECTION_BOX_COLOR),
+            );
+
+            return true;
+        }
+
+        self.selection_box = None;
+
+        if !modifiers.command {
+            self.deselect_all();
+        }
+
+        for object in self.objects_in_rect(Rect::from_two_pos(start.to_pos2(), end.to_pos2())) {
+            self.select_object(object);
+        }
+
+        true
+    }
+
+    /// Shows the fields shared by every selected object, and applies edits to all of them.
+    pub(super) fn process_multi_object_attributes(&mut self, ui: &egui::Ui) {
+        if ui.ctx().input(|i| i.key_pressed(egui::Key::Delete)) {
+            self.history.mark_edited();
+            self.delete_selection();
+            return;
+        }
+
+        if ui.ctx().input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.deselect_all();
+            return;
+        }
+
+        let selected_count = self.selected_objects.len();
+        let mapdata = &mut self.current_mapdata;
+
+        egui::Area::new(egui::Id::from("le_multi_object_attribute_editor"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 10.0))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style())
+                    .inner_margin(egui::Vec2::splat(8.0))
+                    .show(ui, |ui| {
+                        ui.label(format!("Edit {selected_count} objects"));
+
+                        // only fields that every selected object has are shown
+                        let mut collision_types: Vec<&mut String> = mapdata
+                            .walls
+                            .iter_mut()
+                            .filter(|o| o.is_selected)
+                            .map(|o| &mut o.collision_type)
+                            .collect();
+
+                        let mut labels = Vec::new();
+
+                        for wall in mapdata.labeled_walls.iter_mut().filter(|o| o.is_selected) {
+                            collision_types.push(&mut wall.collision_type);
+                            labels.push(&mut wall.label);
+                        }
+
+                        if collision_types.len() == selected_count {
+                            Self::process_shared_collision_type(ui, collision_types);
+                        }
+
+                        if labels.len() == selected_count {
+                            Self::process_shared_text(ui, "Label", labels, 0x20);
+                        }
+
+                        let mut names: Vec<&mut String> = mapdata
+                            .paths
+                            .iter_mut()
+                            .filter(|o| o.is_selected)
+                            .map(|o| &mut o.name)
+                            .chain(
+                                mapdata
+                                    .zones
+                                    .iter_mut()
+                                    .filter(|o| o.is_selected)
+                                    .map(|o| &mut o.name),
+                            )
+                            .chain(
+                                mapdata
+                                    .course_infos
+                                    .iter_mut()
+                                    .filter(|o| o.is_selected)
+                                    .map(|o| &mut o.name),
+                            )
+                            .collect();
+
+                        // gimmick names may be longer than the others
+                        let char_limit = if names.is_empty() { 0x30 } else { 0x20 };
+
+                        names.extend(
+                            mapdata
+                                .gimmicks
+                                .iter_mut()
+                                .filter(|o| o.is_selected)
+                                .map(|o| &mut o.name),
+                        );
+
+                        if names.len() == selected_count {
+                            Self::process_shared_text(ui, "Name", names, char_limit);
+                        }
+
+                        ui.add_space(3.0);
+
+                        ui.label("Drag any selected object to move them all.");
+                    });
+            });
+    }
+
+    fn process_shared_text(
+        ui: &mut egui::Ui,
+        label: &str,
+        values: Vec<&mut String>,
+        char_limit: usize,
+    ) {
+        ui.label(label);
+        Self::process_shared_text_edit(ui, values, char_limit);
+    }
+
+    /// Shows a text field that edits every value at once.
+    /// If the values differ, the field starts out empty.
+    fn process_shared_text_edit(
+        ui: &mut egui::Ui,
+        mut values: Vec<&mut String>,
+        char_limit: usize,
+    ) {
+        let mut text = Self::shared_value(&values).unwrap_or_default();
+
+        let resp = ui.add(
+            egui::TextEdit::singleline(&mut text)
+                .char_limit(char_limit)
+                .hint_text("(mixed)"),
+        );
+
+        if resp.changed() {
+            for value in values.iter_mut() {
+                **value = text.clone();
+            }
+        }
+    }
+
+    fn process_shared_collision_type(ui: &mut egui::Ui, mut values: Vec<&mut String>) {
+        let shared = Self::shared_value(&values);
+
+        egui::ComboBox::from_label("Collision Type")
+            .selected_text(shared.as_deref().unwrap_or("(mixed)"))
+            .show_ui(ui, |ui| {
+                for collision_type in COLLISION_TYPES {
+                    if ui
+                        .selectable_label(shared.as_deref() == Some(collision_type), collision_type)
+                        .clicked()
+                    {
+                        for value in values.iter_mut() {
+                            **value = collision_type.to_string();
+                        }
+                    }
+                }
+            });
+
+        Self::process_shared_text_edit(ui, values, 0x20);
+    }
+
+    /// Returns the value, if every value is the same.
+    fn shared_value(values: &[&mut String]) -> Option<String> {
+        let first = values.first()?;
+
+        values
+            .iter()
+            .all(|value| value == first)
+            .then(|| first.to_string())
+    }
+}
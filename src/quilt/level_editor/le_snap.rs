@@ -0,0 +1,215 @@
+use super::LevelEditor;
+
+use crate::quilt::common::{Camera, ObjectId};
+
+use egui::{Rect, Vec2};
+
+/// The step that Shift locks wall angles to, in degrees.
+const ANGLE_STEP: f32 = 15.0;
+
+/// Grid lines closer together than this, in pixels, aren't drawn.
+const MIN_GRID_SPACING: f32 = 6.0;
+
+pub struct SnapSettings {
+    pub show_grid: bool,
+    pub snap_to_grid: bool,
+    /// The size of one grid cell, in world units.
+    pub grid_size: f32,
+    /// Whether wall endpoints snap to the endpoints of other walls.
+    pub snap_to_vertices: bool,
+    /// How close an endpoint has to be to another to snap to it, in pixels.
+    pub vertex_snap_radius: f32,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            snap_to_grid: false,
+            grid_size: 1.0,
+            snap_to_vertices: true,
+            vertex_snap_radius: 8.0,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// Snaps a position to the grid, if enabled.
+    pub fn snap_point(&self, pos: Vec2) -> Vec2 {
+        if !self.snap_to_grid || self.grid_size <= 0.0 {
+            return pos;
+        }
+
+        (pos / self.grid_size).round() * self.grid_size
+    }
+
+    /// Whether `pos` is close enough to `vertex` to snap to it.
+    pub fn is_in_snap_radius(&self, pos: Vec2, vertex: Vec2, zoom: f32) -> bool {
+        self.snap_to_vertices && (vertex - pos).length() <= self.vertex_snap_radius / zoom
+    }
+
+    /// Finds the closest wall endpoint within the snap radius of `pos`.
+    /// The endpoints of the wall `exclude` are skipped, so that a wall doesn't snap to itself.
+    pub fn snap_to_vertex(
+        &self,
+        pos: Vec2,
+        vertices: &[(ObjectId, Vec2)],
+        exclude: Option<ObjectId>,
+        zoom: f32,
+    ) -> Option<Vec2> {
+        vertices
+            .iter()
+            .filter(|(id, vertex)| {
+                Some(*id) != exclude && self.is_in_snap_radius(pos, *vertex, zoom)
+            })
+            .min_by(|a, b| (a.1 - pos).length().total_cmp(&(b.1 - pos).length()))
+            .map(|(_, vertex)| *vertex)
+    }
+
+    /// Snaps a wall endpoint being moved to `pos`.
+    /// The endpoint snaps to the closest endpoint of another wall within the snap radius.
+    /// Otherwise, it's locked to 15° steps around `other_end` if `lock_angle` is set,
+    /// or snapped to the grid.
+    pub fn snap_endpoint(
+        &self,
+        pos: Vec2,
+        other_end: Vec2,
+        lock_angle: bool,
+        vertices: &[(ObjectId, Vec2)],
+        exclude: Option<ObjectId>,
+        zoom: f32,
+    ) -> Vec2 {
+        if let Some(vertex) = self.snap_to_vertex(pos, vertices, exclude, zoom) {
+            return vertex;
+        }
+
+        if lock_angle {
+            let offset = pos - other_end;
+            let step = ANGLE_STEP.to_radians();
+            let angle = (offset.y.atan2(offset.x) / step).round() * step;
+            let direction = Vec2::angled(angle);
+
+            // keep the length along the locked direction
+            return other_end + direction * offset.dot(direction).max(0.0);
+        }
+
+        self.snap_point(pos)
+    }
+
+    pub fn show_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.show_grid, "Show grid");
+        ui.checkbox(&mut self.snap_to_grid, "Snap to grid");
+
+        ui.horizontal(|ui| {
+            ui.label("Grid size");
+            ui.add(
+                egui::DragValue::new(&mut self.grid_size)
+                    .speed(0.1)
+                    .range(0.1..=100.0),
+            );
+        });
+
+        ui.checkbox(&mut self.snap_to_vertices, "Snap wall endpoints together")
+            .on_hover_text(
+                "Hold Shift while dragging a wall endpoint to lock its angle to 15° steps.",
+            );
+
+        ui.add_enabled_ui(self.snap_to_vertices, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Snap radius (px)");
+                ui.add(
+                    egui::DragValue::new(&mut self.vertex_snap_radius)
+                        .speed(0.5)
+                        .range(1.0..=50.0),
+                );
+            });
+        });
+    }
+}
+
+/// Returns where a point being dragged by `resp` would be without any snapping, in world units.
+/// The offset between the point and the mouse when the drag started is kept,
+/// so that snapping doesn't make the point jump to the mouse.
+pub fn drag_position(
+    ctx: &egui::Context,
+    resp: &egui::Response,
+    camera: &Camera,
+    canvas_rect: Rect,
+    point: Vec2,
+) -> Option<Vec2> {
+    if !resp.dragged() {
+        return None;
+    }
+
+    let pointer = camera.convert_from_camera(resp.interact_pointer_pos()? - canvas_rect.min);
+
+    if resp.drag_started() {
+        ctx.data_mut(|d| d.insert_temp(resp.id, point - pointer));
+    }
+
+    let offset = ctx
+        .data(|d| d.get_temp::<Vec2>(resp.id))
+        .unwrap_or_default();
+
+    Some(pointer + offset)
+}
+
+impl LevelEditor {
+    /// The endpoints of every wall and labeled wall, for endpoints to snap to.
+    pub(super) fn wall_vertices(&self) -> Vec<(ObjectId, Vec2)> {
+        let mapdata = &self.current_mapdata;
+
+        let walls = mapdata
+            .walls
+            .iter()
+            .map(|w| (w.id, w.start, w.end))
+            .chain(mapdata.labeled_walls.iter().map(|w| (w.id, w.start, w.end)));
+
+        walls
+            .flat_map(|(id, start, end)| [(id, start.get_vec2()), (id, end.get_vec2())])
+            .collect()
+    }
+
+    /// Draws the grid behind the level.
+    pub(super) fn draw_grid(&self, ui: &egui::Ui, rect: Rect) {
+        if !self.snap.show_grid || self.snap.grid_size <= 0.0 {
+            return;
+        }
+
+        // skip lines when zoomed out too far to tell them apart
+        let mut step = self.snap.grid_size;
+
+        while step * self.camera.zoom < MIN_GRID_SPACING {
+            step *= 2.0;
+        }
+
+        let painter = ui.painter_at(rect);
+        let top_left = self.camera.convert_from_camera(Vec2::ZERO);
+        let bottom_right = self.camera.convert_from_camera(rect.size());
+
+        let color = egui::Color32::from_rgba_unmultiplied(0xFF, 0xFF, 0xFF, 0x18);
+        let axis_color = egui::Color32::from_rgba_unmultiplied(0xFF, 0xFF, 0xFF, 0x40);
+
+        let stroke = |index: i32| {
+            let color = if index == 0 { axis_color } else { color };
+            egui::Stroke::new(1.0_f32, color)
+        };
+
+        for i in (top_left.x / step).floor() as i32..=(bottom_right.x / step).ceil() as i32 {
+            let x = self
+                .camera
+                .convert_to_camera(Vec2::new(i as f32 * step, 0.0))
+                .x;
+            painter.vline(rect.min.x + x, rect.y_range(), stroke(i));
+        }
+
+        // the y axis is flipped, so the bottom of the screen has the lowest y
+        for i in (bottom_right.y / step).floor() as i32..=(top_left.y / step).ceil() as i32 {
+            let y = self
+                .camera
+                .convert_to_camera(Vec2::new(0.0, i as f32 * step))
+                .y;
+            painter.hline(rect.x_range(), rect.min.y + y, stroke(i));
+        }
+    }
+}
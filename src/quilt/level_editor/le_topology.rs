@@ -0,0 +1,601 @@
+use super::{LevelEditor, ObjectRef};
+
+use crate::quilt::common::Point2D;
+
+use egui::{Rect, Vec2};
+
+/// Points closer together than this are considered the same, in world units.
+const WELD_EPSILON: f32 = 0.001;
+
+const ISSUE_COLOR: egui::Color32 = egui::Color32::from_rgb(0xFF, 0x40, 0x40);
+
+#[derive(Clone, Copy, PartialEq)]
+enum WallEnd {
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IssueKind {
+    ZeroLength,
+    NearMiss,
+    Duplicate,
+    Overlap,
+    TJunction,
+    InconsistentNormal,
+}
+
+impl IssueKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::ZeroLength => "Zero length",
+            Self::NearMiss => "Near miss",
+            Self::Duplicate => "Duplicate",
+            Self::Overlap => "Overlap",
+            Self::TJunction => "T-junction",
+            Self::InconsistentNormal => "Normal",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Fix {
+    Delete(ObjectRef),
+    /// Moves the end of the second wall onto the end of the first.
+    Weld((ObjectRef, WallEnd), (ObjectRef, WallEnd)),
+    Flip(ObjectRef),
+    /// Splits a wall in two at a point.
+    Split(ObjectRef, Vec2),
+}
+
+impl Fix {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Delete(_) => "Delete",
+            Self::Weld(..) => "Weld",
+            Self::Flip(_) => "Flip",
+            Self::Split(..) => "Split",
+        }
+    }
+}
+
+struct Issue {
+    kind: IssueKind,
+    walls: Vec<ObjectRef>,
+    /// Where the issue is, in world units.
+    position: Vec2,
+    fix: Option<Fix>,
+}
+
+pub struct TopologyReport {
+    pub show_panel: bool,
+    issues: Vec<Issue>,
+    /// The walls and near-miss distance the issues were found with.
+    /// The level is analyzed again when either changes, so that fixes don't act on old issues.
+    analyzed: Option<(Vec<Segment>, f32)>,
+    /// Endpoints closer together than this, but not touching, are reported, in world units.
+    near_miss_distance: f32,
+}
+
+impl Default for TopologyReport {
+    fn default() -> Self {
+        Self {
+            show_panel: false,
+            issues: Vec::new(),
+            analyzed: None,
+            near_miss_distance: 0.25,
+        }
+    }
+}
+
+impl TopologyReport {
+    /// Shows or hides the panel. The level is analyzed again when it's shown.
+    pub fn toggle_panel(&mut self) {
+        self.show_panel = !self.show_panel;
+        self.analyzed = None;
+    }
+}
+
+/// A wall or labeled wall, in world units.
+#[derive(PartialEq)]
+pub(super) struct Segment {
+    pub object: ObjectRef,
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Segment {
+    fn end(&self, end: WallEnd) -> Vec2 {
+        match end {
+            WallEnd::Start => self.start,
+            WallEnd::End => self.end,
+        }
+    }
+
+    fn length(&self) -> f32 {
+        (self.end - self.start).length()
+    }
+
+    /// How far along the segment the closest point to `point` is, from 0 to 1.
+    fn project(&self, point: Vec2) -> f32 {
+        let direction = self.end - self.start;
+        ((point - self.start).dot(direction) / direction.length_sq()).clamp(0.0, 1.0)
+    }
+
+    fn distance_to(&self, point: Vec2) -> f32 {
+        let closest = self.start + (self.end - self.start) * self.project(point);
+        (point - closest).length()
+    }
+}
+
+pub(super) fn same_point(a: Vec2, b: Vec2) -> bool {
+    (a - b).length() <= WELD_EPSILON
+}
+
+/// Finds problems with how walls connect to each other.
+fn analyze(segments: &[Segment], near_miss_distance: f32) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let ends = [WallEnd::Start, WallEnd::End];
+
+    for (i, a) in segments.iter().enumerate() {
+        if a.length() <= WELD_EPSILON {
+            issues.push(Issue {
+                kind: IssueKind::ZeroLength,
+                walls: vec![a.object],
+                position: a.start,
+                fix: Some(Fix::Delete(a.object)),
+            });
+
+            continue;
+        }
+
+        for b in segments[i + 1..].iter() {
+            if b.length() <= WELD_EPSILON {
+                continue;
+            }
+
+            let walls = vec![a.object, b.object];
+
+            // the same segment, in either direction
+            if (same_point(a.start, b.start) && same_point(a.end, b.end))
+                || (same_point(a.start, b.end) && same_point(a.end, b.start))
+            {
+                issues.push(Issue {
+                    kind: IssueKind::Duplicate,
+                    walls,
+                    position: (a.start + a.end) / 2.0,
+                    fix: Some(Fix::Delete(b.object)),
+                });
+
+                continue;
+            }
+
+            // collinear segments that share more than a point
+            let direction = (a.end - a.start).normalized();
+            let b_direction = (b.end - b.start).normalized();
+            let cross = direction.x * b_direction.y - direction.y * b_direction.x;
+            let offset = (b.start - a.start) - direction * (b.start - a.start).dot(direction);
+
+            if cross.abs() <= WELD_EPSILON && offset.length() <= WELD_EPSILON {
+                // how far along `a` each end of `b` is
+                let (b_start, b_end) = (
+                    (b.start - a.start).dot(direction),
+                    (b.end - a.start).dot(direction),
+                );
+
+                let overlap_start = b_start.min(b_end).max(0.0);
+                let overlap = b_start.max(b_end).min(a.length()) - overlap_start;
+
+                if overlap > WELD_EPSILON {
+                    issues.push(Issue {
+                        kind: IssueKind::Overlap,
+                        walls,
+                        position: a.start + direction * (overlap_start + overlap / 2.0),
+                        fix: None,
+                    });
+
+                    continue;
+                }
+            }
+
+            for a_end in ends {
+                for b_end in ends {
+                    let (a_point, b_point) = (a.end(a_end), b.end(b_end));
+                    let distance = (a_point - b_point).length();
+
+                    if distance <= WELD_EPSILON {
+                        // connected walls should run in the same direction, end to start,
+                        // so that their normals face the same way
+                        if a_end == b_end {
+                            issues.push(Issue {
+                                kind: IssueKind::InconsistentNormal,
+                                walls: walls.clone(),
+                                position: a_point,
+                                fix: Some(Fix::Flip(b.object)),
+                            });
+                        }
+                    } else if distance <= near_miss_distance {
+                        issues.push(Issue {
+                            kind: IssueKind::NearMiss,
+                            walls: walls.clone(),
+                            position: a_point,
+                            fix: Some(Fix::Weld((a.object, a_end), (b.object, b_end))),
+                        });
+                    }
+                }
+            }
+
+            // an endpoint of one wall that touches the middle of the other
+            for (wall, other) in [(a, b), (b, a)] {
+                for end in ends {
+                    let point = wall.end(end);
+                    let t = other.project(point);
+
+                    if other.distance_to(point) <= WELD_EPSILON
+                        && t * other.length() > near_miss_distance
+                        && (1.0 - t) * other.length() > near_miss_distance
+                    {
+                        issues.push(Issue {
+                            kind: IssueKind::TJunction,
+                            walls: walls.clone(),
+                            position: point,
+                            fix: Some(Fix::Split(other.object, point)),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+impl LevelEditor {
+    pub(super) fn wall_segments(&self) -> Vec<Segment> {
+        let mapdata = &self.current_mapdata;
+
+        let walls = mapdata.walls.iter().map(|w| Segment {
+            object: ObjectRef::Wall(w.id),
+            start: w.start.get_vec2(),
+            end: w.end.get_vec2(),
+        });
+
+        let labeled_walls = mapdata.labeled_walls.iter().map(|w| Segment {
+            object: ObjectRef::LabeledWall(w.id),
+            start: w.start.get_vec2(),
+            end: w.end.get_vec2(),
+        });
+
+        walls.chain(labeled_walls).collect()
+    }
+
+    fn analyze_topology(&mut self) {
+        let (segments, distance) = (self.wall_segments(), self.topology.near_miss_distance);
+
+        self.topology.issues = analyze(&segments, distance);
+        self.topology.analyzed = Some((segments, distance));
+    }
+
+    /// Analyzes the level again if its walls changed since it was last analyzed.
+    fn update_topology(&mut self) {
+        let segments = self.wall_segments();
+        let distance = self.topology.near_miss_distance;
+
+        if self.topology.analyzed.as_ref() != Some(&(segments, distance)) {
+            self.analyze_topology();
+        }
+    }
+
+    /// Returns the start and end of a wall or labeled wall.
+    pub(super) fn wall_points_mut(
+        &mut self,
+        object: ObjectRef,
+    ) -> Option<(&mut Point2D, &mut Point2D)> {
+        let index = self.object_index(object)?;
+        let mapdata = &mut self.current_mapdata;
+
+        match object {
+            ObjectRef::Wall(_) => {
+                let wall = &mut mapdata.walls[index];
+                Some((&mut wall.start, &mut wall.end))
+            }
+
+            ObjectRef::LabeledWall(_) => {
+                let wall = &mut mapdata.labeled_walls[index];
+                Some((&mut wall.start, &mut wall.end))
+            }
+
+            _ => None,
+        }
+    }
+
+    fn apply_fix(&mut self, fix: Fix) {
+        self.deselect_all();
+        self.history.label_next(fix.label());
+
+        match fix {
+            Fix::Delete(object) => {
+                if let Some(index) = self.object_index(object) {
+                    match object {
+                        ObjectRef::Wall(_) => _ = self.current_mapdata.walls.remove(index),
+                        ObjectRef::LabeledWall(_) => {
+                            _ = self.current_mapdata.labeled_walls.remove(index)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            Fix::Weld((target, target_end), (moved, moved_end)) => {
+                let Some((start, end)) = self.wall_points_mut(target) else {
+                    return;
+                };
+
+                let point = match target_end {
+                    WallEnd::Start => *start,
+                    WallEnd::End => *end,
+                };
+
+                if let Some((start, end)) = self.wall_points_mut(moved) {
+                    match moved_end {
+                        WallEnd::Start => *start = point,
+                        WallEnd::End => *end = point,
+                    }
+                }
+            }
+
+            Fix::Flip(object) => {
+                if let Some((start, end)) = self.wall_points_mut(object) {
+                    std::mem::swap(start, end);
+                }
+            }
+
+            Fix::Split(object, point) => {
+                let Some(index) = self.object_index(object) else {
+                    return;
+                };
+
+                let point = Point2D::from_vec2(point);
+                let mapdata = &mut self.current_mapdata;
+
+                match object {
+                    ObjectRef::Wall(_) => {
+                        let mut second = mapdata.walls[index].clone();
+                        second.id = Default::default();
+                        second.start = point;
+                        mapdata.walls[index].end = point;
+                        mapdata.walls.insert(index + 1, second);
+                    }
+
+                    ObjectRef::LabeledWall(_) => {
+                        let mut second = mapdata.labeled_walls[index].clone();
+                        second.id = Default::default();
+                        second.start = point;
+                        mapdata.labeled_walls[index].end = point;
+                        mapdata.labeled_walls.insert(index + 1, second);
+                    }
+
+                    _ => {}
+                }
+            }
+        }
+
+        self.analyze_topology();
+    }
+
+    /// Moves the camera to a point in the level and selects the given objects.
+    pub(super) fn jump_to(&mut self, position: Vec2, objects: &[ObjectRef]) {
+        self.camera.zoom = self.camera.zoom.max(10.0);
+        self.camera.center(position);
+
+        self.deselect_all();
+
+        for object in objects {
+            self.select_object(*object);
+        }
+    }
+
+    pub fn show_topology_ui(&mut self, ui: &mut egui::Ui) {
+        self.update_topology();
+
+        ui.horizontal(|ui| {
+            if ui.button("Analyze").clicked() {
+                self.analyze_topology();
+            }
+
+            ui.label("Near-miss distance");
+            ui.add(
+                egui::DragValue::new(&mut self.topology.near_miss_distance)
+                    .speed(0.01)
+                    .range(0.01..=5.0),
+            );
+        });
+
+        ui.separator();
+
+        if self.topology.issues.is_empty() {
+            ui.label("No issues found.");
+            return;
+        }
+
+        ui.label(format!("{} issues found", self.topology.issues.len()));
+
+        let mut jump = None;
+        let mut fix = None;
+
+        egui::ScrollArea::vertical()
+            .id_salt("le_topology_issues")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for (i, issue) in self.topology.issues.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let text = format!(
+                            "{}  ({:.2}, {:.2})",
+                            issue.kind.name(),
+                            issue.position.x,
+                            issue.position.y
+                        );
+
+                        if ui
+                            .selectable_label(false, text)
+                            .on_hover_text("Go to this issue")
+                            .clicked()
+                        {
+                            jump = Some(i);
+                        }
+
+                        if let Some(issue_fix) = issue.fix
+                            && ui.small_button(issue_fix.label()).clicked()
+                        {
+                            fix = Some(issue_fix);
+                        }
+                    });
+                }
+            });
+
+        if let Some(i) = jump {
+            let issue = &self.topology.issues[i];
+            let (position, walls) = (issue.position, issue.walls.clone());
+            self.jump_to(position, &walls);
+        }
+
+        if let Some(fix) = fix {
+            self.apply_fix(fix);
+        }
+    }
+
+    /// Marks each issue on the canvas.
+    pub(super) fn draw_topology_issues(&self, ui: &egui::Ui, rect: Rect) {
+        if !self.topology.show_panel {
+            return;
+        }
+
+        let painter = ui.painter_at(rect);
+
+        for issue in self.topology.issues.iter() {
+            let pos = rect.min + self.camera.convert_to_camera(issue.position);
+            painter.circle_stroke(pos, 6.0, egui::Stroke::new(1.5_f32, ISSUE_COLOR));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quilt::common::ObjectId;
+
+    fn segment(start: (f32, f32), end: (f32, f32)) -> Segment {
+        Segment {
+            object: ObjectRef::Wall(ObjectId::default()),
+            start: Vec2::new(start.0, start.1),
+            end: Vec2::new(end.0, end.1),
+        }
+    }
+
+    fn kinds(issues: &[Issue]) -> Vec<IssueKind> {
+        issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn connected_walls_have_no_issues() {
+        let segments = [
+            segment((0.0, 0.0), (1.0, 0.0)),
+            segment((1.0, 0.0), (1.0, 1.0)),
+        ];
+
+        assert!(analyze(&segments, 0.25).is_empty());
+    }
+
+    #[test]
+    fn near_miss_is_welded() {
+        let segments = [
+            segment((0.0, 0.0), (1.0, 0.0)),
+            segment((1.1, 0.1), (1.1, 1.0)),
+        ];
+
+        let issues = analyze(&segments, 0.25);
+        assert_eq!(kinds(&issues), [IssueKind::NearMiss]);
+
+        let Some(Fix::Weld((target, WallEnd::End), (moved, WallEnd::Start))) = issues[0].fix else {
+            panic!("expected the end of the first wall to be welded to the second");
+        };
+
+        assert!(target == segments[0].object && moved == segments[1].object);
+    }
+
+    #[test]
+    fn distant_ends_are_not_near_misses() {
+        let segments = [
+            segment((0.0, 0.0), (1.0, 0.0)),
+            segment((1.5, 0.0), (1.5, 1.0)),
+        ];
+
+        assert!(analyze(&segments, 0.25).is_empty());
+    }
+
+    #[test]
+    fn t_junction_splits_the_touched_wall() {
+        let segments = [
+            segment((0.0, 0.0), (2.0, 0.0)),
+            segment((1.0, 0.0), (1.0, 1.0)),
+        ];
+
+        let issues = analyze(&segments, 0.25);
+        assert_eq!(kinds(&issues), [IssueKind::TJunction]);
+
+        let Some(Fix::Split(object, point)) = issues[0].fix else {
+            panic!("expected the first wall to be split");
+        };
+
+        assert!(object == segments[0].object);
+        assert_eq!(point, Vec2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn t_junction_near_an_end_is_left_to_near_misses() {
+        let segments = [
+            segment((0.0, 0.0), (2.0, 0.0)),
+            segment((1.9, 0.0), (1.9, 1.0)),
+        ];
+
+        assert!(!kinds(&analyze(&segments, 0.25)).contains(&IssueKind::TJunction));
+    }
+
+    #[test]
+    fn overlap_is_found_in_the_shared_part() {
+        let segments = [
+            segment((0.0, 0.0), (2.0, 0.0)),
+            segment((1.0, 0.0), (3.0, 0.0)),
+        ];
+
+        let issues = analyze(&segments, 0.25);
+        assert_eq!(kinds(&issues), [IssueKind::Overlap]);
+        assert_eq!(issues[0].position, Vec2::new(1.5, 0.0));
+    }
+
+    #[test]
+    fn collinear_walls_end_to_end_do_not_overlap() {
+        let segments = [
+            segment((0.0, 0.0), (1.0, 0.0)),
+            segment((1.0, 0.0), (2.0, 0.0)),
+        ];
+
+        assert!(analyze(&segments, 0.25).is_empty());
+    }
+
+    #[test]
+    fn duplicates_and_zero_length_walls_are_deleted() {
+        let segments = [
+            segment((0.0, 0.0), (1.0, 0.0)),
+            segment((1.0, 0.0), (0.0, 0.0)),
+            segment((5.0, 5.0), (5.0, 5.0)),
+        ];
+
+        let issues = analyze(&segments, 0.25);
+        assert_eq!(
+            kinds(&issues),
+            [IssueKind::Duplicate, IssueKind::ZeroLength]
+        );
+        assert!(matches!(issues[0].fix, Some(Fix::Delete(o)) if o == segments[1].object));
+    }
+}
@@ -1,4 +1,4 @@
-use super::{EditMode, LevelEditor, ObjectIndex};
+use super::{EditMode, LevelEditor, ObjectRef};
 
 use anyhow::{Context, Result, bail};
 use reqwest::blocking::Client;
@@ -96,49 +96,52 @@ impl LevelEditor {
         }
     }
 
-    pub fn deselect_all(&mut self) {
-        for select_type in self.selected_object_indices.iter() {
-            match select_type {
-                ObjectIndex::Wall(index) => {
-                    self.current_mapdata.walls[*index].is_selected = false;
-                }
-
-                ObjectIndex::LabeledWall(index) => {
-                    self.current_mapdata.labeled_walls[*index].is_selected = false;
-                }
-
-                ObjectIndex::CommonGimmick(index) => {
-                    self.current_mapdata.common_gimmicks[*index].is_selected = false;
-                }
-
-                ObjectIndex::Gimmick(index) => {
-                    self.current_mapdata.gimmicks[*index].is_selected = false;
-                }
-
-                ObjectIndex::Path(index) => {
-                    self.current_mapdata.paths[*index].is_selected = false;
-                }
-
-                ObjectIndex::Zone(index) => {
-                    self.current_mapdata.zones[*index].is_selected = false;
-                }
-
-                ObjectIndex::CourseInfo(index) => {
-                    self.current_mapdata.course_infos[*index].is_selected = false;
-                }
-
-                ObjectIndex::Enemy(index) => {
-                    self.current_endata.enemies[*index].is_selected = false;
-                }
+    /// Finds where an object currently is in its list.
+    pub(super) fn object_index(&self, object: ObjectRef) -> Option<usize> {
+        let mapdata = &self.current_mapdata;
+
+        match object {
+            ObjectRef::Wall(id) => mapdata.walls.iter().position(|o| o.id == id),
+            ObjectRef::LabeledWall(id) => mapdata.labeled_walls.iter().position(|o| o.id == id),
+            ObjectRef::CommonGimmick(id) => mapdata.common_gimmicks.iter().position(|o| o.id == id),
+            ObjectRef::Gimmick(id) => mapdata.gimmicks.iter().position(|o| o.id == id),
+            ObjectRef::Path(id) => mapdata.paths.iter().position(|o| o.id == id),
+            ObjectRef::Zone(id) => mapdata.zones.iter().position(|o| o.id == id),
+            ObjectRef::CourseInfo(id) => mapdata.course_infos.iter().position(|o| o.id == id),
+            ObjectRef::Enemy(id) => self.current_endata.enemies.iter().position(|o| o.id == id),
+            ObjectRef::Comment(id) => self.comments.as_ref()?.iter().position(|o| o.id == id),
+        }
+    }
 
-                ObjectIndex::Comment(index) => {
-                    if let Some(comments) = self.comments.as_mut() {
-                        comments[*index].is_selected = false;
-                    }
+    /// Sets whether an object is drawn as selected.
+    pub(super) fn set_object_selected(&mut self, object: ObjectRef, is_selected: bool) {
+        let Some(index) = self.object_index(object) else {
+            return;
+        };
+
+        let mapdata = &mut self.current_mapdata;
+
+        match object {
+            ObjectRef::Wall(_) => mapdata.walls[index].is_selected = is_selected,
+            ObjectRef::LabeledWall(_) => mapdata.labeled_walls[index].is_selected = is_selected,
+            ObjectRef::CommonGimmick(_) => mapdata.common_gimmicks[index].is_selected = is_selected,
+            ObjectRef::Gimmick(_) => mapdata.gimmicks[index].is_selected = is_selected,
+            ObjectRef::Path(_) => mapdata.paths[index].is_selected = is_selected,
+            ObjectRef::Zone(_) => mapdata.zones[index].is_selected = is_selected,
+            ObjectRef::CourseInfo(_) => mapdata.course_infos[index].is_selected = is_selected,
+            ObjectRef::Enemy(_) => self.current_endata.enemies[index].is_selected = is_selected,
+            ObjectRef::Comment(_) => {
+                if let Some(comments) = self.comments.as_mut() {
+                    comments[index].is_selected = is_selected;
                 }
             }
         }
-        self.selected_object_indices.clear();
+    }
+
+    pub fn deselect_all(&mut self) {
+        for object in std::mem::take(&mut self.selected_objects) {
+            self.set_object_selected(object, false);
+        }
     }
 
     pub fn update_object_data(&mut self) -> Result<(), anyhow::Error> {
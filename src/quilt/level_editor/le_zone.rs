@@ -0,0 +1,160 @@
+use super::le_snap::{SnapSettings, drag_position};
+
+use crate::quilt::{
+    common::{Camera, Point2D},
+    game::mapdata::Zone,
+};
+
+use egui::{Pos2, Rect, Vec2};
+
+const HANDLE_COLOR: egui::Color32 = egui::Color32::from_rgb(0x00, 0x9F, 0xFD);
+
+/// The size of a resize handle, in pixels.
+const HANDLE_SIZE: f32 = 7.0;
+
+/// Which side of the zone a handle moves along one axis.
+#[derive(Clone, Copy)]
+enum Side {
+    Min,
+    Max,
+    /// The handle doesn't move this axis.
+    Neither,
+}
+
+/// Moves whichever of `start` and `end` is on `side` of the other to `value`.
+/// A side can't be moved past the opposite one.
+fn move_side(start: &mut f32, end: &mut f32, side: Side, value: f32) {
+    let (min, max) = if *start <= *end {
+        (start, end)
+    } else {
+        (end, start)
+    };
+
+    match side {
+        Side::Min => *min = value.min(*max),
+        Side::Max => *max = value.max(*min),
+        Side::Neither => {}
+    }
+}
+
+fn side_value(min: f32, max: f32, side: Side) -> f32 {
+    match side {
+        Side::Min => min,
+        Side::Max => max,
+        Side::Neither => (min + max) / 2.0,
+    }
+}
+
+/// Draws handles on the corners and edges of a rectangle that resize it when dragged,
+/// along with its size.
+pub(super) fn process_resize_handles(
+    ui: &egui::Ui,
+    canvas_rect: Rect,
+    camera: &Camera,
+    snap: &SnapSettings,
+    id: egui::Id,
+    [start, end]: [&mut Point2D; 2],
+    color: egui::Color32,
+) {
+    let painter = ui.painter_at(canvas_rect);
+    let (min, max) = (
+        start.get_vec2().min(end.get_vec2()),
+        start.get_vec2().max(end.get_vec2()),
+    );
+
+    // the y axis points up in the level, so the minimum y is the bottom edge
+    let handles = [
+        (Side::Min, Side::Max, egui::CursorIcon::ResizeNorthWest),
+        (Side::Neither, Side::Max, egui::CursorIcon::ResizeNorth),
+        (Side::Max, Side::Max, egui::CursorIcon::ResizeNorthEast),
+        (Side::Max, Side::Neither, egui::CursorIcon::ResizeEast),
+        (Side::Max, Side::Min, egui::CursorIcon::ResizeSouthEast),
+        (Side::Neither, Side::Min, egui::CursorIcon::ResizeSouth),
+        (Side::Min, Side::Min, egui::CursorIcon::ResizeSouthWest),
+        (Side::Min, Side::Neither, egui::CursorIcon::ResizeWest),
+    ];
+
+    for (i, (x_side, y_side, cursor)) in handles.into_iter().enumerate() {
+        let point = Vec2::new(
+            side_value(min.x, max.x, x_side),
+            side_value(min.y, max.y, y_side),
+        );
+
+        let pos = canvas_rect.min + camera.convert_to_camera(point);
+        let handle_rect = Rect::from_center_size(pos, Vec2::splat(HANDLE_SIZE));
+
+        painter.rect_filled(handle_rect, 1.0, color);
+
+        let resp = ui
+            .interact(
+                canvas_rect.intersect(handle_rect.expand(2.0)),
+                id.with(i),
+                egui::Sense::drag(),
+            )
+            .on_hover_cursor(cursor);
+
+        if let Some(pos) = drag_position(ui.ctx(), &resp, camera, canvas_rect, point) {
+            let target = snap.snap_point(pos);
+
+            move_side(&mut start.x, &mut end.x, x_side, target.x);
+            move_side(&mut start.y, &mut end.y, y_side, target.y);
+        }
+    }
+
+    let size = max - min;
+    let bottom =
+        canvas_rect.min + camera.convert_to_camera(Vec2::new((min.x + max.x) / 2.0, min.y));
+
+    painter.text(
+        bottom + Vec2::new(0.0, HANDLE_SIZE + 2.0),
+        egui::Align2::CENTER_TOP,
+        format!("{:.2} × {:.2}", size.x, size.y),
+        egui::FontId::proportional(12.0),
+        egui::Color32::WHITE,
+    );
+}
+
+/// Draws handles on the corners and edges of a selected zone that resize it when dragged.
+pub(super) fn process_zone_handles(
+    ui: &egui::Ui,
+    canvas_rect: Rect,
+    camera: &Camera,
+    snap: &SnapSettings,
+    zone: &mut Zone,
+) {
+    process_resize_handles(
+        ui,
+        canvas_rect,
+        camera,
+        snap,
+        egui::Id::new((zone.id, "resize")),
+        [&mut zone.bounds_start, &mut zone.bounds_end],
+        HANDLE_COLOR,
+    );
+}
+
+/// Draws the zone's name, and the unknown string at 0x20 if it's set, in its top left corner.
+pub(super) fn draw_zone_name(
+    painter: &egui::Painter,
+    zone: &Zone,
+    square: Rect,
+    color: egui::Color32,
+) {
+    // names are only drawn where they fit
+    if square.width() < 24.0 || square.height() < 14.0 {
+        return;
+    }
+
+    let painter = painter.with_clip_rect(painter.clip_rect().intersect(square.shrink(1.0)));
+    let font = egui::FontId::proportional(12.0);
+    let mut pos = square.min + Vec2::splat(3.0);
+
+    for text in [&zone.name, &zone.unk_20] {
+        if text.is_empty() {
+            continue;
+        }
+
+        let rect = painter.text(pos, egui::Align2::LEFT_TOP, text, font.clone(), color);
+        pos = Pos2::new(pos.x, rect.max.y + 1.0);
+    }
+}
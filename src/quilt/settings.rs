@@ -1,7 +1,8 @@
 use anyhow::Result;
 use eframe::egui;
+use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
-use std::{env, fmt::Display, fs};
+use std::{env, fmt::Display, fs, path::PathBuf};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -59,6 +60,10 @@ pub struct LevelEditorSettings {
     // Note: Move this out of "Level Editor Settings" and into "General" when writing new camera-based editors.
     #[serde(default)]
     pub zoom_type: ZoomType,
+    /// The folder the game's files were extracted to.
+    /// When set, backgrounds are searched for in it when a level is opened.
+    #[serde(default)]
+    pub game_root: Option<PathBuf>,
 }
 
 impl Default for LevelEditorSettings {
@@ -66,6 +71,7 @@ impl Default for LevelEditorSettings {
         Self {
             snap_to_start: true,
             zoom_type: ZoomType::TowardsMouse,
+            game_root: None,
         }
     }
 }
@@ -173,5 +179,34 @@ impl QuiltSettings {
                     );
                 }
             });
+
+        ui.horizontal(|ui| {
+            ui.label("Game Dump");
+
+            match &self.level_editor_settings.game_root {
+                Some(path) => ui.label(path.display().to_string()),
+                None => ui.weak("Not set"),
+            };
+
+            if ui.button("Browse").clicked()
+                && let Some(path) = FileDialog::new().pick_folder()
+            {
+                self.level_editor_settings.game_root = Some(path);
+            }
+
+            if ui
+                .add_enabled(
+                    self.level_editor_settings.game_root.is_some(),
+                    egui::Button::new("Clear"),
+                )
+                .clicked()
+            {
+                self.level_editor_settings.game_root = None;
+            }
+        })
+        .response
+        .on_hover_text(
+            "The folder the game's files were extracted to. When a level is opened, its background will be searched for here.",
+        );
     }
 }
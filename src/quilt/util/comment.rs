@@ -1,7 +1,7 @@
-use crate::quilt::common::Point2D;
+use crate::quilt::common::{ObjectId, Point2D};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Comment {
     /// The file this comment is for, e.g. "file.mapbin"
     pub file: String,
@@ -10,4 +10,6 @@ pub struct Comment {
 
     #[serde(skip)] // editor-only, don't include in file
     pub is_selected: bool,
+    #[serde(skip)]
+    pub id: ObjectId,
 }